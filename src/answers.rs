@@ -0,0 +1,196 @@
+//! Known-good answers, kept in a small TOML file so refactors of shared code
+//! can be checked against them:
+//!
+//! ```toml
+//! [day2]
+//! part_one = "3306701"
+//! part_two = "7621"
+//! ```
+//!
+//! Only what that needs is supported: `[dayN]` tables, keys set to basic
+//! strings (with `\n`, `\"` and `\\` escapes), comments and blank lines.
+
+extern crate advent;
+use self::advent::*;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RecordedAnswers {
+    answers: BTreeMap<(u32, Part), Answer>,
+}
+
+/// The outcome of checking an answer against the recorded one.
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Pass,
+    Changed { expected: Answer },
+    Unrecorded,
+}
+
+impl RecordedAnswers {
+    pub fn parse(contents: &str) -> Result<RecordedAnswers, String> {
+        let mut recorded = RecordedAnswers::default();
+        let mut day = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            let error = |message: &str| format!("Line {}: {}", index + 1, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                let number = line.strip_prefix("[day").and_then(|rest| rest.strip_suffix(']'))
+                    .and_then(|number| number.parse::<u32>().ok())
+                    .ok_or_else(|| error(&format!("expecting a table like [day1], got {}", line)))?;
+                day = Some(number);
+                continue;
+            }
+
+            let equals = line.find('=').ok_or_else(|| error("expecting key = \"value\""))?;
+            let part = match line[..equals].trim() {
+                "part_one" => Part::One,
+                "part_two" => Part::Two,
+                key => return Err(error(&format!("unknown key {}", key))),
+            };
+            let day = day.ok_or_else(|| error("answer outside of a [dayN] table"))?;
+            let value = unescape(line[equals + 1..].trim()).map_err(|message| error(&message))?;
+
+            recorded.answers.insert((day, part), Answer::from(value));
+        }
+
+        Ok(recorded)
+    }
+
+    pub fn load(path: &str) -> Result<RecordedAnswers, String> {
+        let contents = read_inputs(path)?;
+        RecordedAnswers::parse(&contents).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|error| format!("Couldn't write {}: {}", path, error))
+    }
+
+    pub fn get(&self, day: u32, part: Part) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    pub fn record(&mut self, day: u32, answers: &Answers) {
+        for part in &Part::all() {
+            self.answers.insert((day, *part), answers.get(*part).clone());
+        }
+    }
+
+    pub fn check(&self, day: u32, part: Part, answer: &Answer) -> Check {
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Check::Pass,
+            Some(expected) => Check::Changed { expected: expected.clone() },
+            None => Check::Unrecorded,
+        }
+    }
+}
+
+impl fmt::Display for RecordedAnswers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut current_day = None;
+        for (&(day, part), answer) in &self.answers {
+            if current_day != Some(day) {
+                if current_day.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{}]", day)?;
+                current_day = Some(day);
+            }
+            let key = match part {
+                Part::One => "part_one",
+                Part::Two => "part_two",
+            };
+            writeln!(f, "{} = {}", key, escape(answer.as_str()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes an answer as a TOML basic string.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn unescape(quoted: &str) -> Result<String, String> {
+    let inner = quoted.strip_prefix('"').and_then(|rest| rest.strip_suffix('"'))
+        .ok_or(format!("expecting a quoted string, got {}", quoted))?;
+
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                other => return Err(format!("unsupported escape \\{}", other.map(String::from).unwrap_or_default())),
+            },
+            '"' => return Err(format!("unescaped quote in {}", quoted)),
+            c => value.push(c),
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: &str = "# Known-good answers\n[day1]\npart_one = \"3287620\"\npart_two = \"4928567\"\n\n[day8]\npart_one = \"1920\"\npart_two = \"# \\\\\\n \\\"#\"\n";
+
+    #[test]
+    fn test_parse() {
+        let recorded = RecordedAnswers::parse(ANSWERS).unwrap();
+
+        assert_eq!(Some(&Answer::from(3287620)), recorded.get(1, Part::One));
+        assert_eq!(Some(&Answer::from("# \\\n \"#")), recorded.get(8, Part::Two));
+        assert_eq!(None, recorded.get(2, Part::One));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Err("Line 1: answer outside of a [dayN] table".to_string()), RecordedAnswers::parse("part_one = \"1\""));
+        assert_eq!(Err("Line 2: unknown key part_three".to_string()), RecordedAnswers::parse("[day1]\npart_three = \"1\""));
+        assert!(RecordedAnswers::parse("[dayone]").is_err());
+        assert!(RecordedAnswers::parse("[day1]\npart_one = 1").is_err());
+        assert!(RecordedAnswers::parse("[day1]\npart_one = \"\\t\"").is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let recorded = RecordedAnswers::parse(ANSWERS).unwrap();
+
+        assert_eq!(ANSWERS.trim_start_matches("# Known-good answers\n"), recorded.to_string());
+        assert_eq!(recorded, RecordedAnswers::parse(&recorded.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_record_and_check() {
+        let mut recorded = RecordedAnswers::default();
+        recorded.record(2, &Answers { part_one: Answer::from(1), part_two: Answer::from(2) });
+
+        assert_eq!(Check::Pass, recorded.check(2, Part::One, &Answer::from(1)));
+        assert_eq!(Check::Changed { expected: Answer::from(2) }, recorded.check(2, Part::Two, &Answer::from(3)));
+        assert_eq!(Check::Unrecorded, recorded.check(3, Part::One, &Answer::from(1)));
+    }
+}
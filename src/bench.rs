@@ -0,0 +1,284 @@
+//! Benchmarks each part of a day against its real input: run it a number of
+//! times, then report timing statistics and how much it allocated per run.
+//!
+//! Results can be saved to a baseline file and later runs compared against
+//! it. The file has one line per part:
+//!
+//! ```text
+//! # day part mean_ns median_ns p95_ns allocations bytes
+//! 2 2 6240112 6198734 6530021 10 8192
+//! ```
+
+extern crate advent;
+use self::advent::*;
+
+use days::Day;
+use runner::format_duration;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_RUNS: usize = 10;
+
+/// Wraps the system allocator to count allocations, installed as the global
+/// allocator by `main`. It only counts once `bench_day` turns counting on, so
+/// other commands just pay for checking a flag.
+pub struct CountingAllocator;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+fn count_allocation(bytes: usize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+fn allocation_counts() -> (usize, usize) {
+    (ALLOCATIONS.load(Ordering::Relaxed), ALLOCATED_BYTES.load(Ordering::Relaxed))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    /// Allocations per run.
+    pub allocations: usize,
+    /// Bytes allocated per run.
+    pub bytes: usize,
+}
+
+/// Stats for one part of one day.
+#[derive(Debug, PartialEq)]
+pub struct Measurement {
+    pub day: u32,
+    pub part: Part,
+    pub stats: Stats,
+}
+
+pub fn bench_day(day: &Day, input: &str, runs: usize) -> Result<Vec<Measurement>, ParseError> {
+    COUNTING.store(true, Ordering::Relaxed);
+    let mut measurements = vec![];
+    (day.measure)(input, &mut |part, run| {
+        let mut samples = Vec::with_capacity(runs);
+        let (allocations_before, bytes_before) = allocation_counts();
+        for _ in 0..runs {
+            let start = Instant::now();
+            let answer = run();
+            samples.push(start.elapsed());
+            drop(answer);
+        }
+        let (allocations_after, bytes_after) = allocation_counts();
+        // Pushing the samples never allocates, having reserved them upfront.
+        let allocations = (allocations_after - allocations_before) / runs.max(1);
+        let bytes = (bytes_after - bytes_before) / runs.max(1);
+
+        measurements.push(Measurement { day: day.number, part, stats: stats(&mut samples, allocations, bytes) });
+    })?;
+    Ok(measurements)
+}
+
+pub fn stats(samples: &mut [Duration], allocations: usize, bytes: usize) -> Stats {
+    samples.sort();
+    let mean = match samples.len() {
+        0 => Duration::from_secs(0),
+        len => samples.iter().sum::<Duration>() / len as u32,
+    };
+
+    Stats {
+        mean,
+        median: percentile(samples, 50),
+        p95: percentile(samples, 95),
+        allocations,
+        bytes,
+    }
+}
+
+// Nearest rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::from_secs(0);
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+pub type Baseline = BTreeMap<(u32, Part), Stats>;
+
+pub fn parse_baseline(contents: &str) -> Result<Baseline, String> {
+    let mut baseline = Baseline::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let numbers: Vec<u64> = line.split_whitespace().map(|field| field.parse::<u64>()).collect::<Result<_, _>>()
+            .map_err(|_| format!("Line {}: expecting numbers, got {}", index + 1, line))?;
+        if numbers.len() != 7 {
+            return Err(format!("Line {}: expecting 7 fields, got {}", index + 1, numbers.len()));
+        }
+        let part = match numbers[1] {
+            1 => Part::One,
+            2 => Part::Two,
+            other => return Err(format!("Line {}: unknown part {}", index + 1, other)),
+        };
+        baseline.insert((numbers[0] as u32, part), Stats {
+            mean: Duration::from_nanos(numbers[2]),
+            median: Duration::from_nanos(numbers[3]),
+            p95: Duration::from_nanos(numbers[4]),
+            allocations: numbers[5] as usize,
+            bytes: numbers[6] as usize,
+        });
+    }
+    Ok(baseline)
+}
+
+pub fn format_baseline(measurements: &[Measurement]) -> String {
+    let mut contents = String::from("# day part mean_ns median_ns p95_ns allocations bytes\n");
+    for measurement in measurements {
+        let stats = &measurement.stats;
+        contents.push_str(&format!("{} {} {} {} {} {} {}\n",
+            measurement.day, measurement.part.number(), stats.mean.as_nanos(), stats.median.as_nanos(),
+            stats.p95.as_nanos(), stats.allocations, stats.bytes));
+    }
+    contents
+}
+
+pub fn load_baseline(path: &str) -> Result<Baseline, String> {
+    parse_baseline(&read_inputs(path)?).map_err(|error| format!("{}: {}", path, error))
+}
+
+pub fn save_baseline(path: &str, measurements: &[Measurement]) -> Result<(), String> {
+    fs::write(path, format_baseline(measurements)).map_err(|error| format!("Couldn't write {}: {}", path, error))
+}
+
+/// A table of the measurements. With a baseline, the change in median time
+/// against it is added for the parts it has.
+pub fn bench_table(measurements: &[Measurement], baseline: Option<&Baseline>) -> String {
+    let mut header = format!("{:<5} {:<5} {:>12} {:>12} {:>12} {:>10} {:>12}",
+        "Day", "Part", "Mean", "Median", "p95", "Allocs", "Bytes");
+    if baseline.is_some() {
+        header.push_str(&format!(" {:>10}", "vs base"));
+    }
+
+    let mut lines = vec![header];
+    for measurement in measurements {
+        let stats = &measurement.stats;
+        let mut line = format!("{:<5} {:<5} {:>12} {:>12} {:>12} {:>10} {:>12}",
+            measurement.day, measurement.part.number(), format_duration(stats.mean), format_duration(stats.median),
+            format_duration(stats.p95), stats.allocations, stats.bytes);
+        if let Some(baseline) = baseline {
+            let change = baseline.get(&(measurement.day, measurement.part))
+                .map(|base| format_change(base.median, stats.median))
+                .unwrap_or_else(|| "new".to_string());
+            line.push_str(&format!(" {:>10}", change));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn format_change(before: Duration, after: Duration) -> String {
+    if before.as_nanos() == 0 {
+        return "n/a".to_string();
+    }
+    let change = (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0;
+    format!("{:+.1}%", change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    fn measurement(day: u32, part: Part, median_ms: u64) -> Measurement {
+        let median = Duration::from_millis(median_ms);
+        Measurement { day, part, stats: Stats { mean: median, median, p95: median, allocations: 3, bytes: 96 } }
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = stats(&mut millis(&[5, 1, 4, 2, 3, 100, 6, 7, 8, 9]), 2, 64);
+
+        assert_eq!(Duration::from_micros(14500), stats.mean);
+        assert_eq!(Duration::from_millis(5), stats.median);
+        assert_eq!(Duration::from_millis(100), stats.p95);
+        assert_eq!((2, 64), (stats.allocations, stats.bytes));
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(Duration::from_millis(1), percentile(&millis(&[1]), 95));
+        assert_eq!(Duration::from_millis(2), percentile(&millis(&[1, 2, 3]), 50));
+        assert_eq!(Duration::from_secs(0), percentile(&[], 50));
+    }
+
+    #[test]
+    fn test_bench_day() {
+        let day = ::days::find_day(1).unwrap();
+        let measurements = bench_day(&day, "12\n14\n", 3).unwrap();
+
+        assert_eq!(vec![(1, Part::One), (1, Part::Two)],
+            measurements.iter().map(|m| (m.day, m.part)).collect::<Vec<(u32, Part)>>());
+    }
+
+    #[test]
+    fn test_bench_day_parse_error() {
+        let day = ::days::find_day(1).unwrap();
+
+        assert!(bench_day(&day, "12\nfourteen\n", 3).is_err());
+    }
+
+    #[test]
+    fn test_baseline_round_trips() {
+        let measurements = vec![measurement(2, Part::One, 3), measurement(2, Part::Two, 40)];
+        let baseline = parse_baseline(&format_baseline(&measurements)).unwrap();
+
+        assert_eq!(Some(&measurements[1].stats), baseline.get(&(2, Part::Two)));
+        assert_eq!(2, baseline.len());
+    }
+
+    #[test]
+    fn test_parse_baseline_errors() {
+        assert!(parse_baseline("1 1 2 3").is_err());
+        assert!(parse_baseline("1 3 1 1 1 1 1").is_err());
+        assert!(parse_baseline("1 1 fast 1 1 1 1").is_err());
+    }
+
+    #[test]
+    fn test_bench_table() {
+        let mut baseline = Baseline::new();
+        baseline.insert((2, Part::One), measurement(2, Part::One, 4).stats);
+        let table = bench_table(&[measurement(2, Part::One, 3), measurement(2, Part::Two, 40)], Some(&baseline));
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert!(lines[0].ends_with("vs base"));
+        assert!(lines[1].ends_with("-25.0%"));
+        assert!(lines[2].ends_with("new"));
+    }
+}
@@ -1,7 +1,60 @@
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day8;
\ No newline at end of file
+extern crate advent;
+use self::advent::*;
+
+/// A registered day: its number and how to solve it from the puzzle input.
+pub struct Day {
+    pub number: u32,
+    pub solve: fn(&str) -> Result<Solved, ParseError>,
+    pub measure: fn(&str, Measure) -> Result<(), ParseError>,
+}
+
+// Declares each day's module and registers its solution. Adding a day is one
+// line here.
+macro_rules! days {
+    ($($number:expr => $module:ident::$solution:ident),* $(,)*) => {
+        $(pub mod $module;)*
+
+        pub fn registry() -> Vec<Day> {
+            vec![$(Day {
+                number: $number,
+                solve: solve_timed::<$module::$solution>,
+                measure: measure_parts::<$module::$solution>,
+            }),*]
+        }
+    };
+}
+
+days! {
+    1 => day1::Day1,
+    2 => day2::Day2,
+    3 => day3::Day3,
+    4 => day4::Day4,
+    5 => day5::Day5,
+    6 => day6::Day6,
+    8 => day8::Day8,
+}
+
+pub fn find_day(number: u32) -> Option<Day> {
+    registry().into_iter().find(|day| day.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_sorted_and_unique() {
+        let numbers: Vec<u32> = registry().iter().map(|day| day.number).collect();
+        let mut expected = numbers.clone();
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(expected, numbers);
+    }
+
+    #[test]
+    fn test_find_day() {
+        assert_eq!(Some(3), find_day(3).map(|day| day.number));
+        assert!(find_day(7).is_none());
+    }
+}
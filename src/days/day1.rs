@@ -1,15 +1,22 @@
 extern crate advent;
 use self::advent::*;
 
-pub fn run() {
-    let filename = "inputs/day1.txt";
-    
-    let inputs = split_lines_into_vec_int(&read_inputs(filename));
-    let part_one = run_function_and_sum_all(calculate_fuel, &inputs);
-    println!("Part One: {}", part_one);
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+        parse_lines(input)
+    }
+
+    fn part_one(inputs: &Vec<i64>) -> Answer {
+        run_function_and_sum_all(calculate_fuel, inputs).into()
+    }
 
-    let part_two = run_function_and_sum_all(calculate_fuel_including_own_fuel_mass, &inputs);
-    println!("Part Two: {}", part_two);
+    fn part_two(inputs: &Vec<i64>) -> Answer {
+        run_function_and_sum_all(calculate_fuel_including_own_fuel_mass, inputs).into()
+    }
 }
 
 fn calculate_fuel(module: &i64) -> i64 {
@@ -55,4 +62,12 @@ mod tests {
     fn test_calculate_fuel_including_own_fuel_mass_complex() {
         assert_eq!(50346, calculate_fuel_including_own_fuel_mass(&100756));
     }
+
+    #[test]
+    fn test_solve() {
+        let answers = solve::<Day1>("12\n14\n1969\n100756\n").unwrap();
+
+        assert_eq!(Answer::from(2 + 2 + 654 + 33583), answers.part_one);
+        assert_eq!(Answer::from(2 + 2 + 966 + 50346), answers.part_two);
+    }
 }
\ No newline at end of file
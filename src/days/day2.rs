@@ -1,15 +1,22 @@
 extern crate advent;
 use self::advent::*;
 
-pub fn run() {
-    let filename = "inputs/day2.txt";
-    let inputs = split_into_vec_usize(&read_inputs(filename).trim(), ",");
+pub struct Day2;
 
-    let part_one = run_program_changing_params(&inputs, &12, &2);
-    println!("Part One: {}", part_one);
+impl Solution for Day2 {
+    type Input = Vec<usize>;
 
-    let part_two = find_noun_verb_calc_for_output(&inputs, &19690720);
-    println!("Part Two: {}", part_two);
+    fn parse(input: &str) -> Result<Vec<usize>, ParseError> {
+        parse_list(input, ",")
+    }
+
+    fn part_one(inputs: &Vec<usize>) -> Answer {
+        run_program_changing_params(inputs, &12, &2).into()
+    }
+
+    fn part_two(inputs: &Vec<usize>) -> Answer {
+        find_noun_verb_calc_for_output(inputs, &19690720).into()
+    }
 }
 
 fn run_program_changing_params(inputs: &Vec<usize>, noun: &usize, verb: &usize) -> usize {
@@ -1,116 +1,77 @@
 extern crate advent;
 use self::advent::*;
 
-use std::collections::HashSet;
+use self::advent::passwords::{Passwords, Rule};
 
-pub fn run() {
-    let filename = "inputs/day4.txt";
+pub struct Day4;
 
-    let inputs = read_inputs(filename);
-    let inputs : Vec<&str> = inputs.trim().split("-").collect();
+impl Solution for Day4 {
+    type Input = (u128, u128);
 
-    let min = inputs.get(0).expect("Couldn't get minimum value").parse::<i32>().expect("Expected integer");
-    let max = inputs.get(1).expect("Couldn't get maximum value").parse::<i32>().expect("Expected integer");
-
-    let all_passwords = calculate_passwords(6);
-    let filtered_passwords = all_passwords.filter_within_range(&min, &max);
-    let part_one = filtered_passwords.len();
-    println!("Part one: {}", part_one);
-
-    let filtered_passwords = filtered_passwords.filter_just_with_alone_doubles();
-    let part_two = filtered_passwords.len();
-    println!("Part two: {}", part_two);
-}
-
-fn calculate_passwords(max_length: usize) -> Vec<i32> {
-    let mut last_passwords = vec![11, 22, 33, 44, 55, 66, 77, 88, 99];
-
-    for _ in 2..max_length {
-
-        let mut passwords = HashSet::new();
-        for new_digit in 1..10 {
-
-            let possible_passwords : &Vec<i32> = &last_passwords
-            .iter()
-            .filter(|pwd| new_digit >= last_digit(pwd))
-            .map(|pwd| format!("{}{}", pwd, new_digit).parse::<i32>().unwrap())
-            .collect();
-
-            passwords.extend(possible_passwords);
-
-            let possible_passwords : &Vec<i32> = &last_passwords
-            .iter()
-            .filter(|pwd| first_digit(pwd) >= new_digit)
-            .map(|pwd| format!("{}{}", new_digit, pwd).parse::<i32>().unwrap())
-            .collect();
-
-            passwords.extend(possible_passwords);
+    fn parse(input: &str) -> Result<(u128, u128), ParseError> {
+        match parse_list::<u128>(input, "-")?.as_slice() {
+            [min, max] => Ok((*min, *max)),
+            [_] => Err(ParseError::end_of_input(input, "the maximum value")),
+            _ => Err(ParseError::at(input, 0, input.trim(), "a range like 123-456")),
+        }
     }
 
-    last_passwords = passwords.into_iter().collect();
+    fn part_one((min, max): &(u128, u128)) -> Answer {
+        passwords(Rule::RunAtLeast(2)).count_between(*min, *max).expect("Six digits can't overflow").into()
     }
 
-    last_passwords.sort();
-    return last_passwords;
-}
-
-fn first_digit(num: &i32) -> i32 {
-    return num / (10 as i32).pow((*num as f32).log10() as u32);
+    fn part_two((min, max): &(u128, u128)) -> Answer {
+        passwords(Rule::RunExactly(2)).count_between(*min, *max).expect("Six digits can't overflow").into()
+    }
 }
 
-fn last_digit(num: &i32) -> i32 {
-    return num % 10;
+// Six digit passwords whose digits never decrease, with the part's rule about
+// repeated digits.
+fn passwords(double: Rule) -> Passwords {
+    Passwords::new(6, 10).with_rule(Rule::NonDecreasing).with_rule(double)
 }
 
-fn has_alone_double(num: &i32) -> bool {
-    let num_vec : Vec<char> = num.to_string().chars().collect();
-
-    let mut has_alone_double = (num_vec[0] == num_vec[1] && num_vec[1] != num_vec[2]) || (num_vec[4] == num_vec[5] && num_vec[3] != num_vec[4]);
-    for i in 1..4 {
-        has_alone_double = has_alone_double || (num_vec[i-1] != num_vec[i] && num_vec[i] == num_vec[i+1] && num_vec[i+1] != num_vec[i+2]);
-    }
-    return has_alone_double;
-}
+#[cfg(test)]
 
-pub trait RangeFilter {
-    fn filter_within_range(&self, min: &i32, max: &i32) -> Vec<i32>;
+mod tests {
+    use super::*;
 
-    fn filter_just_with_alone_doubles(&self) -> Vec<i32>;
-}
+    #[test]
+    fn test_has_double() {
+        let passwords = passwords(Rule::RunAtLeast(2));
 
-impl RangeFilter for Vec<i32> {
-    fn filter_within_range(&self, min: &i32, max: &i32) -> Vec<i32> {
-        return self.clone()
-        .into_iter()
-        .filter(|pwd| pwd >= &min && pwd <= &max)
-        .collect();
+        assert_eq!(true, passwords.is_valid(111111));
+        assert_eq!(false, passwords.is_valid(223450));
+        assert_eq!(false, passwords.is_valid(123789));
     }
 
-    fn filter_just_with_alone_doubles(&self) -> Vec<i32> {
-        return self.clone()
-        .into_iter()
-        .filter(|pwd| has_alone_double(pwd))
-        .collect();
-    }
-}
+    #[test]
+    fn test_has_alone_double() {
+        let passwords = passwords(Rule::RunExactly(2));
 
-#[cfg(test)]
+        assert_eq!(true, passwords.is_valid(112233));
+        assert_eq!(false, passwords.is_valid(123444));
+        assert_eq!(true, passwords.is_valid(111122));
+    }
 
-mod tests {
-    use super::*;
-    
     #[test]
-    fn test_first_digit() {
-        assert_eq!(9, first_digit(&98317237));
-        assert_eq!(8, first_digit(&82));
-        assert_eq!(1, first_digit(&1231449999));
+    fn test_count_agrees_with_enumeration() {
+        let (min, max) = Day4::parse(&read_inputs("inputs/day4.txt").unwrap()).unwrap();
+        for double in [Rule::RunAtLeast(2), Rule::RunExactly(2)] {
+            let passwords = passwords(double);
+            let valid = passwords.valid_between(min, max);
+
+            assert_eq!(Some(valid.len() as u128), passwords.count_between(min, max));
+        }
     }
 
     #[test]
-    fn test_has_alone_double() {
-        assert_eq!(true, has_alone_double(&112233));
-        assert_eq!(false, has_alone_double(&123444));
-        assert_eq!(true, has_alone_double(&111122));
+    fn test_parse() {
+        assert_eq!(Ok((172930, 683082)), Day4::parse("172930-683082\n"));
+        assert_eq!("Expected u128 at line 1, column 8, got '68x'", Day4::parse("172930-68x").unwrap_err().to_string());
+        assert!(Day4::parse("172930").is_err());
+        assert_eq!(Ok((100000000000000000000, 999999999999999999999)),
+            Day4::parse("100000000000000000000-999999999999999999999"));
     }
 
-}
\ No newline at end of file
+}
@@ -1,13 +1,22 @@
 extern crate advent;
 use self::advent::*;
 
-pub fn run() {
-    let filename = "inputs/day5.txt";
-    let inputs = split_into_vec_int(&read_inputs(filename).trim(), ",");
+pub struct Day5;
 
-    let part_one = intcode::run_program(&inputs, &1);
-    println!("Part one: {}", part_one.output.last().unwrap());
+impl Solution for Day5 {
+    type Input = Vec<i64>;
 
-    let part_two = intcode::run_program(&inputs, &5);
-    println!("Part two: {}", part_two.output.last().unwrap());
+    fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+        parse_list(input, ",")
+    }
+
+    fn part_one(inputs: &Vec<i64>) -> Answer {
+        let part_one = intcode::run_program(inputs, &1);
+        (*part_one.output.last().unwrap()).into()
+    }
+
+    fn part_two(inputs: &Vec<i64>) -> Answer {
+        let part_two = intcode::run_program(inputs, &5);
+        (*part_two.output.last().unwrap()).into()
+    }
 }
\ No newline at end of file
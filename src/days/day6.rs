@@ -3,19 +3,26 @@ use self::advent::*;
 
 use std::collections::HashMap;
 
-pub fn run() {
-    let filename = "inputs/day6.txt";
-    let inputs = read_inputs(filename);
-    let inputs = split_lines_into_vec_str(&inputs);
+pub struct Day6;
 
-    let orbits_tree = get_orbits_tree(&inputs);
-    let part_one = get_total_number_of_orbits(&orbits_tree);
+impl Solution for Day6 {
+    type Input = Vec<String>;
 
-    println!("Part one: {}", part_one);
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        Ok(split_lines_into_vec_str(input).into_iter().map(|line| line.to_string()).collect())
+    }
 
-    let part_two = get_distance_between_you_and_san(&orbits_tree);
+    fn part_one(inputs: &Vec<String>) -> Answer {
+        let inputs = inputs.iter().map(|line| line.as_str()).collect();
+        let orbits_tree = get_orbits_tree(&inputs);
+        get_total_number_of_orbits(&orbits_tree).into()
+    }
 
-    println!("Part two: {}", part_two);
+    fn part_two(inputs: &Vec<String>) -> Answer {
+        let inputs = inputs.iter().map(|line| line.as_str()).collect();
+        let orbits_tree = get_orbits_tree(&inputs);
+        get_distance_between_you_and_san(&orbits_tree).into()
+    }
 }
 
 fn get_orbits_tree<'a>(inputs: &'a Vec<&str>) -> HashMap<&'a str, Vec<&'a str>> {
@@ -1,28 +1,32 @@
 extern crate advent;
 use self::advent::*;
+use self::advent::grid::Grid;
 
 use std::collections::HashMap;
 
-extern crate ansi_term;
-use self::ansi_term::Colour::*;
-
 
 type CountsType = HashMap<char, usize>;
 const LAYER_WIDTH : usize = 25;
 const LAYER_HEIGHT : usize = 6;
 
-pub fn run() {
-    let filename = "inputs/day8.txt";
-    let inputs = read_inputs(filename);
-    let inputs = inputs.trim();
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = String;
+
+    fn parse(input: &str) -> Result<String, ParseError> {
+        Ok(input.trim().to_string())
+    }
 
-    let part_one = run_part_one(inputs, LAYER_WIDTH, LAYER_HEIGHT);
-    println!("Part one: {}", part_one);
+    fn part_one(inputs: &String) -> Answer {
+        run_part_one(inputs, LAYER_WIDTH, LAYER_HEIGHT).into()
+    }
 
-    println!("Part two:");
-    let layers = split_into_layers(inputs, LAYER_WIDTH, LAYER_HEIGHT);
-    let image = stack_layers(&layers);
-    print_image(image.as_str(), LAYER_WIDTH, LAYER_HEIGHT);
+    fn part_two(inputs: &String) -> Answer {
+        let layers = split_into_layers(inputs, LAYER_WIDTH, LAYER_HEIGHT);
+        let image = stack_layers(&layers);
+        render_image(image.as_str(), LAYER_WIDTH, LAYER_HEIGHT).into()
+    }
 }
 
 fn run_part_one(input: &str, layer_width: usize, layer_height: usize) -> usize {
@@ -66,17 +70,10 @@ fn stack_layers(layers: &Vec<&str>) -> String {
     image
 }
 
-fn print_image(image: &str, layer_width: usize, layer_height: usize) {
-    for i in 0..layer_height {
-        for c in image.get(layer_width*i..layer_width*(i+1)).unwrap().chars() {
-            if c == '0' {
-                print!("{}", Black.on(Black).paint(c.to_string()));  
-            } else {
-                print!("{}", White.on(White).paint(c.to_string()));  
-            }
-        }
-        println!();
-    }
+fn render_image(image: &str, layer_width: usize, layer_height: usize) -> String {
+    Grid::from_cells(layer_width, layer_height, image.chars().collect())
+    .expect("Image doesn't match the layer size")
+    .render(|&c| if c == '0' { ' ' } else { '█' })
 }
 
 fn split_into_layers(input: &str, layer_width: usize, layer_height: usize) -> Vec<&str> {
@@ -128,6 +125,11 @@ mod tests {
         assert_eq!(1, run_part_one("123456789012", 3, 2));
     }
 
+    #[test]
+    fn test_render_image() {
+        assert_eq!(" █\n█ ", render_image("0110", 2, 2));
+    }
+
     #[test]
     fn test_stack_layers() {
         let layers = vec!["0222", "1122", "2212", "0000"];
@@ -0,0 +1,878 @@
+//! Integer geometry: points and vectors in 2D and 3D, compass directions,
+//! distances, segments along the axes and diagonals, and bounding boxes.
+//!
+//! Coordinates can be any signed integer type, defaulting to `i64`. Unlike
+//! `Grid`, `y` grows upwards here, so `Direction::Up` adds one to it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub};
+
+/// A signed integer usable as a coordinate.
+pub trait Coordinate: Copy + Ord + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Rem<Output = Self> + Neg<Output = Self> {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            fn zero() -> $t { 0 }
+            fn one() -> $t { 1 }
+            fn abs(self) -> $t { <$t>::abs(self) }
+            fn signum(self) -> $t { <$t>::signum(self) }
+            fn to_f64(self) -> f64 { self as f64 }
+        })*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T = i64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector3<T = i64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coordinate> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Point<T> {
+        Point::new(T::zero(), T::zero())
+    }
+
+    pub fn manhattan_distance(&self, other: &Point<T>) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev_distance(&self, other: &Point<T>) -> T {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    pub fn euclidean_distance(&self, other: &Point<T>) -> f64 {
+        (*self - *other).length()
+    }
+}
+
+impl<T: Coordinate> Vector<T> {
+    pub fn new(x: T, y: T) -> Vector<T> {
+        Vector { x, y }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.x.to_f64().hypot(self.y.to_f64())
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+
+    pub fn origin() -> Point3<T> {
+        Point3::new(T::zero(), T::zero(), T::zero())
+    }
+
+    pub fn manhattan_distance(&self, other: &Point3<T>) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    pub fn chebyshev_distance(&self, other: &Point3<T>) -> T {
+        (self.x - other.x).abs().max((self.y - other.y).abs()).max((self.z - other.z).abs())
+    }
+
+    pub fn euclidean_distance(&self, other: &Point3<T>) -> f64 {
+        (*self - *other).length()
+    }
+}
+
+impl<T: Coordinate> Vector3<T> {
+    pub fn new(x: T, y: T, z: T) -> Vector3<T> {
+        Vector3 { x, y, z }
+    }
+
+    pub fn length(&self) -> f64 {
+        let (x, y, z) = (self.x.to_f64(), self.y.to_f64(), self.z.to_f64());
+        (x * x + y * y + z * z).sqrt()
+    }
+}
+
+// Points move by vectors, and are apart by a vector; vectors add and scale.
+macro_rules! arithmetic {
+    ($point:ident, $vector:ident, $($field:ident),*) => {
+        impl<T: Coordinate> Add<$vector<T>> for $point<T> {
+            type Output = $point<T>;
+            fn add(self, vector: $vector<T>) -> $point<T> {
+                $point { $($field: self.$field + vector.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> AddAssign<$vector<T>> for $point<T> {
+            fn add_assign(&mut self, vector: $vector<T>) {
+                *self = *self + vector;
+            }
+        }
+
+        impl<T: Coordinate> Sub for $point<T> {
+            type Output = $vector<T>;
+            fn sub(self, other: $point<T>) -> $vector<T> {
+                $vector { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> Add for $vector<T> {
+            type Output = $vector<T>;
+            fn add(self, other: $vector<T>) -> $vector<T> {
+                $vector { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> Mul<T> for $vector<T> {
+            type Output = $vector<T>;
+            fn mul(self, scale: T) -> $vector<T> {
+                $vector { $($field: self.$field * scale),* }
+            }
+        }
+
+        impl<T: Coordinate> Neg for $vector<T> {
+            type Output = $vector<T>;
+            fn neg(self) -> $vector<T> {
+                $vector { $($field: -self.$field),* }
+            }
+        }
+    };
+}
+
+arithmetic!(Point, Vector, x, y);
+arithmetic!(Point3, Vector3, x, y, z);
+
+/// The four orthogonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [Direction::Up, Direction::Right, Direction::Down, Direction::Left]
+    }
+
+    /// Parses `U`, `R`, `D`, `L` or their compass equivalents `N`, `E`, `S`,
+    /// `W`.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | 'N' => Some(Direction::Up),
+            'R' | 'E' => Some(Direction::Right),
+            'D' | 'S' => Some(Direction::Down),
+            'L' | 'W' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.turn_right().turn_right()
+    }
+
+    /// A vector of length one in this direction.
+    pub fn vector<T: Coordinate>(self) -> Vector<T> {
+        match self {
+            Direction::Up => Vector::new(T::zero(), T::one()),
+            Direction::Right => Vector::new(T::one(), T::zero()),
+            Direction::Down => Vector::new(T::zero(), -T::one()),
+            Direction::Left => Vector::new(-T::one(), T::zero()),
+        }
+    }
+}
+
+/// The eight compass directions, diagonals included, clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    pub fn all() -> [Direction8; 8] {
+        [
+            Direction8::North, Direction8::NorthEast, Direction8::East, Direction8::SouthEast,
+            Direction8::South, Direction8::SouthWest, Direction8::West, Direction8::NorthWest,
+        ]
+    }
+
+    /// Parses `N`, `NE`, `E`, `SE`, `S`, `SW`, `W` or `NW`, also accepting
+    /// `U`, `R`, `D` and `L` for the orthogonal ones.
+    pub fn from_name(name: &str) -> Option<Direction8> {
+        match name {
+            "N" | "U" => Some(Direction8::North),
+            "NE" => Some(Direction8::NorthEast),
+            "E" | "R" => Some(Direction8::East),
+            "SE" => Some(Direction8::SouthEast),
+            "S" | "D" => Some(Direction8::South),
+            "SW" => Some(Direction8::SouthWest),
+            "W" | "L" => Some(Direction8::West),
+            "NW" => Some(Direction8::NorthWest),
+            _ => None,
+        }
+    }
+
+    /// Turns clockwise by `eighths` of a full turn, or anticlockwise for
+    /// negative values.
+    pub fn rotate(self, eighths: i32) -> Direction8 {
+        let index = Direction8::all().iter().position(|&direction| direction == self).unwrap() as i32;
+        Direction8::all()[(index + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Direction8 {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Direction8 {
+        self.rotate(-2)
+    }
+
+    pub fn reverse(self) -> Direction8 {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        let vector = self.vector::<i64>();
+        vector.x != 0 && vector.y != 0
+    }
+
+    /// A vector one step in this direction, so diagonals move along both axes.
+    pub fn vector<T: Coordinate>(self) -> Vector<T> {
+        let (zero, one) = (T::zero(), T::one());
+        match self {
+            Direction8::North => Vector::new(zero, one),
+            Direction8::NorthEast => Vector::new(one, one),
+            Direction8::East => Vector::new(one, zero),
+            Direction8::SouthEast => Vector::new(one, -one),
+            Direction8::South => Vector::new(zero, -one),
+            Direction8::SouthWest => Vector::new(-one, -one),
+            Direction8::West => Vector::new(-one, zero),
+            Direction8::NorthWest => Vector::new(-one, one),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Direction8 {
+        match direction {
+            Direction::Up => Direction8::North,
+            Direction::Right => Direction8::East,
+            Direction::Down => Direction8::South,
+            Direction::Left => Direction8::West,
+        }
+    }
+}
+
+/// A horizontal, vertical or 45° diagonal segment between two points, both
+/// included. Only whole points count as on it, so two diagonals crossing
+/// between points don't meet. Segments are only made through `new`, so they
+/// always run one of those ways.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment<T = i64> {
+    start: Point<T>,
+    end: Point<T>,
+}
+
+impl<T: Coordinate> Segment<T> {
+    /// The segment from `start` to `end`, if they're on the same row, column
+    /// or diagonal.
+    pub fn new(start: Point<T>, end: Point<T>) -> Option<Segment<T>> {
+        let offset = end - start;
+        if offset.x == T::zero() || offset.y == T::zero() || offset.x.abs() == offset.y.abs() {
+            Some(Segment { start, end })
+        } else {
+            None
+        }
+    }
+
+    pub fn start(&self) -> Point<T> {
+        self.start
+    }
+
+    pub fn end(&self) -> Point<T> {
+        self.end
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        !self.is_horizontal() && !self.is_vertical()
+    }
+
+    /// The number of steps from one end to the other, a diagonal step
+    /// counting as one.
+    pub fn length(&self) -> T {
+        self.start.chebyshev_distance(&self.end)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox<T> {
+        BoundingBox::new(self.start, self.end)
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        let (a, b, c) = self.line();
+        a * point.x + b * point.y == c && self.bounding_box().contains(point)
+    }
+
+    /// How many steps along the segment from its start `point` is, if it's on
+    /// it.
+    pub fn distance_along(&self, point: &Point<T>) -> Option<T> {
+        if self.contains(point) {
+            Some(self.start.chebyshev_distance(point))
+        } else {
+            None
+        }
+    }
+
+    /// The vector of one step from the start towards the end.
+    pub fn step(&self) -> Vector<T> {
+        Vector::new((self.end.x - self.start.x).signum(), (self.end.y - self.start.y).signum())
+    }
+
+    /// Every point on the segment, in order from its start.
+    pub fn points(&self) -> Vec<Point<T>> {
+        let step = self.step();
+        let mut points = vec![self.start];
+        let mut point = self.start;
+        while point != self.end {
+            point += step;
+            points.push(point);
+        }
+        points
+    }
+
+    /// Where two segments meet: a single point (as a segment of length zero),
+    /// the stretch two collinear segments share, or `None`.
+    pub fn intersection(&self, other: &Segment<T>) -> Option<Segment<T>> {
+        let overlap = self.bounding_box().intersection(&other.bounding_box())?;
+        if self.start == self.end || other.start == other.end {
+            let point = if self.start == self.end { self.start } else { other.start };
+            return if self.contains(&point) && other.contains(&point) { Some(Segment { start: point, end: point }) } else { None };
+        }
+
+        let (a, b, c) = self.line();
+        let (other_a, other_b, other_c) = other.line();
+        if (a, b) == (other_a, other_b) {
+            // On the same line, the overlap of the bounding boxes has the
+            // shared stretch as its diagonal, or as the whole box along an
+            // axis.
+            if c != other_c {
+                return None;
+            }
+            let falling = a == T::one() && b == T::one();
+            return if falling {
+                Some(Segment { start: Point::new(overlap.min.x, overlap.max.y), end: Point::new(overlap.max.x, overlap.min.y) })
+            } else {
+                Some(Segment { start: overlap.min, end: overlap.max })
+            };
+        }
+
+        let determinant = a * other_b - other_a * b;
+        let x = c * other_b - other_c * b;
+        let y = a * other_c - other_a * c;
+        if x % determinant != T::zero() || y % determinant != T::zero() {
+            return None;
+        }
+        let point = Point::new(x / determinant, y / determinant);
+        if self.contains(&point) && other.contains(&point) {
+            Some(Segment { start: point, end: point })
+        } else {
+            None
+        }
+    }
+
+    // The line through the segment, as `(a, b, c)` with `a * x + b * y = c`.
+    // Single points are taken as horizontal.
+    fn line(&self) -> (T, T, T) {
+        let (zero, one) = (T::zero(), T::one());
+        let (a, b) = if self.is_horizontal() {
+            (zero, one)
+        } else if self.is_vertical() {
+            (one, zero)
+        } else if (self.end.x - self.start.x).signum() == (self.end.y - self.start.y).signum() {
+            (-one, one)
+        } else {
+            (one, one)
+        };
+        (a, b, a * self.start.x + b * self.start.y)
+    }
+}
+
+/// Every pair of segments that meet, as their indices (lowest first) and
+/// where they meet, sorted by index. Rather than trying every pair, a line is
+/// swept across the plane keeping the horizontal segments it's over, so this
+/// stays fast for hundreds of thousands of segments. Diagonal segments are
+/// only checked against those whose bounding boxes they overlap.
+pub fn segment_intersections<T: Coordinate>(segments: &[Segment<T>]) -> Vec<(usize, usize, Segment<T>)> {
+    let mut pairs = crossing_pairs(segments);
+    pairs.extend(collinear_pairs(segments, true));
+    pairs.extend(collinear_pairs(segments, false));
+    if segments.iter().any(Segment::is_diagonal) {
+        pairs.extend(diagonal_pairs(segments).0);
+    }
+    pairs.sort();
+    pairs.dedup();
+
+    pairs.into_iter()
+        .filter_map(|(a, b)| segments[a].intersection(&segments[b]).map(|overlap| (a, b, overlap)))
+        .collect()
+}
+
+// Horizontal segments crossing vertical ones. Single points count as
+// horizontal. At each x, segments are added before the vertical ones there
+// are checked, and removed after.
+fn crossing_pairs<T: Coordinate>(segments: &[Segment<T>]) -> Vec<(usize, usize)> {
+    const ADD: u8 = 0;
+    const CHECK: u8 = 1;
+    const REMOVE: u8 = 2;
+
+    let mut events = vec![];
+    for (index, segment) in segments.iter().enumerate() {
+        let bounds = segment.bounding_box();
+        if segment.is_horizontal() {
+            events.push((bounds.min.x, ADD, index));
+            events.push((bounds.max.x, REMOVE, index));
+        } else if segment.is_vertical() {
+            events.push((bounds.min.x, CHECK, index));
+        }
+    }
+    events.sort();
+
+    let mut active: BTreeMap<T, Vec<usize>> = BTreeMap::new();
+    let mut pairs = vec![];
+    for (_, event, index) in events {
+        let bounds = segments[index].bounding_box();
+        match event {
+            ADD => active.entry(bounds.min.y).or_default().push(index),
+            REMOVE => {
+                let at_y = active.get_mut(&bounds.min.y).expect("Removing a segment that was never added");
+                at_y.retain(|&other| other != index);
+                if at_y.is_empty() {
+                    active.remove(&bounds.min.y);
+                }
+            }
+            _ => {
+                for (_, horizontal) in active.range(bounds.min.y..=bounds.max.y) {
+                    pairs.extend(horizontal.iter().map(|&other| (other.min(index), other.max(index))));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+// Overlapping segments on the same row, or the same column.
+fn collinear_pairs<T: Coordinate>(segments: &[Segment<T>], horizontal: bool) -> Vec<(usize, usize)> {
+    let mut lines: BTreeMap<T, Vec<(T, T, usize)>> = BTreeMap::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let bounds = segment.bounding_box();
+        if horizontal && segment.is_horizontal() {
+            lines.entry(bounds.min.y).or_default().push((bounds.min.x, bounds.max.x, index));
+        } else if !horizontal && !segment.is_horizontal() && segment.is_vertical() {
+            lines.entry(bounds.min.x).or_default().push((bounds.min.y, bounds.max.y, index));
+        }
+    }
+
+    let mut pairs = vec![];
+    for (_, mut intervals) in lines {
+        intervals.sort();
+        let mut open: Vec<(T, usize)> = vec![];
+        for (start, end, index) in intervals {
+            open.retain(|&(open_end, _)| open_end >= start);
+            pairs.extend(open.iter().map(|&(_, other)| (other.min(index), other.max(index))));
+            open.push((end, index));
+        }
+    }
+    pairs
+}
+
+// Diagonal segments against any segment whose bounding box they overlap,
+// sweeping across x so only boxes that overlap there are compared. Axis
+// aligned segments never need checking against each other here, so they're
+// only compared with the diagonals open when they start, and only tidied
+// away when a diagonal starts and has to look at them. Also says how many
+// open segments were looked at along the way.
+fn diagonal_pairs<T: Coordinate>(segments: &[Segment<T>]) -> (Vec<(usize, usize)>, usize) {
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by_key(|&index| segments[index].bounding_box().min.x);
+
+    let mut open_diagonals: Vec<usize> = vec![];
+    let mut open_aligned: Vec<usize> = vec![];
+    let mut pairs = vec![];
+    let mut examined = 0;
+    let check = |index: usize, others: &[usize], pairs: &mut Vec<(usize, usize)>, examined: &mut usize| {
+        *examined += others.len();
+        let bounds = segments[index].bounding_box();
+        for &other in others {
+            if bounds.intersection(&segments[other].bounding_box()).is_some() {
+                pairs.push((other.min(index), other.max(index)));
+            }
+        }
+    };
+    for index in order {
+        let min_x = segments[index].bounding_box().min.x;
+        let still_open = |other: &usize| segments[*other].bounding_box().max.x >= min_x;
+        examined += open_diagonals.len();
+        open_diagonals.retain(still_open);
+        check(index, &open_diagonals, &mut pairs, &mut examined);
+        if segments[index].is_diagonal() {
+            examined += open_aligned.len();
+            open_aligned.retain(still_open);
+            check(index, &open_aligned, &mut pairs, &mut examined);
+            open_diagonals.push(index);
+        } else {
+            open_aligned.push(index);
+        }
+    }
+    (pairs, examined)
+}
+
+/// The smallest axis-aligned rectangle holding some points, edges included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox<T = i64> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Coordinate> BoundingBox<T> {
+    /// The box with `a` and `b` as opposite corners.
+    pub fn new(a: Point<T>, b: Point<T>) -> BoundingBox<T> {
+        BoundingBox {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = Point<T>>>(points: I) -> Option<BoundingBox<T>> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(BoundingBox::new(first, first), |bounds, point| bounds.extend(point)))
+    }
+
+    pub fn extend(&self, point: Point<T>) -> BoundingBox<T> {
+        BoundingBox::new(
+            Point::new(self.min.x.min(point.x), self.min.y.min(point.y)),
+            Point::new(self.max.x.max(point.x), self.max.y.max(point.y)),
+        )
+    }
+
+    pub fn union(&self, other: &BoundingBox<T>) -> BoundingBox<T> {
+        self.extend(other.min).extend(other.max)
+    }
+
+    pub fn intersection(&self, other: &BoundingBox<T>) -> Option<BoundingBox<T>> {
+        let min = Point::new(self.min.x.max(other.min.x), self.min.y.max(other.min.y));
+        let max = Point::new(self.max.x.min(other.max.x), self.max.y.min(other.max.y));
+        if min.x <= max.x && min.y <= max.y {
+            Some(BoundingBox { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        self.min.x <= point.x && point.x <= self.max.x && self.min.y <= point.y && point.y <= self.max.y
+    }
+
+    /// The number of columns covered.
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::one()
+    }
+
+    /// The number of rows covered.
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+
+        assert_eq!(7, a.manhattan_distance(&b));
+        assert_eq!(4, a.chebyshev_distance(&b));
+        assert_eq!(5.0, a.euclidean_distance(&b));
+        assert_eq!(170, Point::<i32>::origin().manhattan_distance(&Point::new(158, -12)));
+    }
+
+    #[test]
+    fn test_distances_3d() {
+        let a = Point3::new(1, 2, 3);
+        let b = Point3::new(3, 5, 9);
+
+        assert_eq!(11, a.manhattan_distance(&b));
+        assert_eq!(6, a.chebyshev_distance(&b));
+        assert_eq!(7.0, a.euclidean_distance(&b));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut point = Point::new(1, 1) + Vector::new(2, -3) * 2;
+        assert_eq!(Point::new(5, -5), point);
+
+        point += -Vector::new(5, -5);
+        assert_eq!(Point::origin(), point);
+        assert_eq!(Vector::new(3, 4), Point::new(4, 6) - Point::new(1, 2));
+        assert_eq!(Point3::new(2, 3, 4), Point3::new(1, 1, 1) + Vector3::new(1, 2, 3));
+    }
+
+    #[test]
+    fn test_direction_turning() {
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+        assert_eq!(Direction::Up, Direction::Down.reverse());
+        for direction in &Direction::all() {
+            assert_eq!(*direction, direction.turn_left().turn_right());
+            assert_eq!(-direction.vector::<i32>(), direction.reverse().vector());
+        }
+    }
+
+    #[test]
+    fn test_direction_from_char() {
+        assert_eq!(Some(Direction::Up), Direction::from_char('U'));
+        assert_eq!(Some(Direction::Left), Direction::from_char('W'));
+        assert_eq!(None, Direction::from_char('X'));
+        assert_eq!(Vector::new(0, -1), Direction::Down.vector::<i64>());
+    }
+
+    #[test]
+    fn test_direction8() {
+        assert_eq!(Direction8::SouthWest, Direction8::NorthEast.reverse());
+        assert_eq!(Direction8::SouthEast, Direction8::NorthEast.turn_right());
+        assert_eq!(Direction8::NorthWest, Direction8::North.rotate(-1));
+        assert_eq!(Some(Direction8::NorthWest), Direction8::from_name("NW"));
+        assert_eq!(None, Direction8::from_name("NN"));
+        assert_eq!(Vector::new(-1, -1), Direction8::SouthWest.vector::<i64>());
+        assert!(Direction8::SouthWest.is_diagonal());
+        assert_eq!(Direction8::West, Direction::Left.into());
+    }
+
+    #[test]
+    fn test_segment() {
+        let segment = Segment::new(Point::new(10, 50), Point::new(10, 0)).unwrap();
+
+        assert!(segment.is_vertical());
+        assert_eq!(50, segment.length());
+        assert!(segment.contains(&Point::new(10, 25)));
+        assert!(!segment.contains(&Point::new(10, 51)));
+        assert_eq!(Some(25), segment.distance_along(&Point::new(10, 25)));
+        assert_eq!(Vector::new(0, -1), segment.step());
+        assert_eq!(None, Segment::new(Point::new(0, 0), Point::new(1, 2)));
+    }
+
+    #[test]
+    fn test_diagonal_segment() {
+        let segment = Segment::new(Point::new(2, 2), Point::new(-3, -3)).unwrap();
+
+        assert!(segment.is_diagonal());
+        assert_eq!(5, segment.length());
+        assert!(segment.contains(&Point::new(0, 0)));
+        assert!(!segment.contains(&Point::new(0, 1)));
+        assert_eq!(Some(3), segment.distance_along(&Point::new(-1, -1)));
+        assert_eq!(vec![Point::new(0, 4), Point::new(1, 3), Point::new(2, 2)], Segment::new(Point::new(0, 4), Point::new(2, 2)).unwrap().points());
+    }
+
+    #[test]
+    fn test_diagonal_segment_intersection() {
+        let rising = Segment::new(Point::new(0, 0), Point::new(10, 10)).unwrap();
+        let point = |x, y| Some(Segment { start: Point::new(x, y), end: Point::new(x, y) });
+
+        assert_eq!(point(3, 3), rising.intersection(&Segment::new(Point::new(0, 6), Point::new(6, 0)).unwrap()));
+        assert_eq!(point(4, 4), rising.intersection(&Segment::new(Point::new(-5, 4), Point::new(5, 4)).unwrap()));
+        assert_eq!(point(7, 7), rising.intersection(&Segment::new(Point::new(7, 20), Point::new(7, 0)).unwrap()));
+        // Crossing at (2.5, 2.5), between points.
+        assert_eq!(None, rising.intersection(&Segment::new(Point::new(0, 5), Point::new(5, 0)).unwrap()));
+        assert_eq!(None, rising.intersection(&Segment::new(Point::new(0, 1), Point::new(5, 6)).unwrap()));
+        assert_eq!(
+            Some(Segment { start: Point::new(8, 8), end: Point::new(10, 10) }),
+            rising.intersection(&Segment::new(Point::new(12, 12), Point::new(8, 8)).unwrap())
+        );
+
+        let falling = Segment::new(Point::new(0, 10), Point::new(10, 0)).unwrap();
+        assert_eq!(
+            Some(Segment { start: Point::new(6, 4), end: Point::new(10, 0) }),
+            falling.intersection(&Segment::new(Point::new(6, 4), Point::new(12, -2)).unwrap())
+        );
+        assert_eq!(point(5, 5), falling.intersection(&Segment::new(Point::new(5, 5), Point::new(5, 5)).unwrap()));
+    }
+
+    #[test]
+    fn test_segment_points() {
+        let segment = Segment::new(Point::new(3, 1), Point::new(0, 1)).unwrap();
+
+        assert_eq!(vec![Point::new(3, 1), Point::new(2, 1), Point::new(1, 1), Point::new(0, 1)], segment.points());
+        assert_eq!(vec![Point::new(5, 5)], Segment::new(Point::new(5, 5), Point::new(5, 5)).unwrap().points());
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        let vertical = Segment::new(Point::new(10, 0), Point::new(10, 50)).unwrap();
+        let horizontal = Segment::new(Point::new(25, 25), Point::new(-25, 25)).unwrap();
+        let crossing = Point::new(10, 25);
+
+        assert_eq!(Some(Segment { start: crossing, end: crossing }), vertical.intersection(&horizontal));
+
+        let overlapping = Segment::new(Point::new(10, 60), Point::new(10, 40)).unwrap();
+        assert_eq!(
+            Some(Segment { start: Point::new(10, 40), end: Point::new(10, 50) }),
+            vertical.intersection(&overlapping)
+        );
+
+        let touching = Segment::new(Point::new(10, 50), Point::new(30, 50)).unwrap();
+        assert_eq!(vec![Point::new(10, 50)], vertical.intersection(&touching).unwrap().points());
+
+        let apart = Segment::new(Point::new(11, 0), Point::new(11, 50)).unwrap();
+        assert_eq!(None, vertical.intersection(&apart));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounds = BoundingBox::from_points(vec![Point::new(3, -1), Point::new(-2, 4), Point::new(0, 0)]).unwrap();
+
+        assert_eq!(BoundingBox { min: Point::new(-2, -1), max: Point::new(3, 4) }, bounds);
+        assert_eq!((6, 6), (bounds.width(), bounds.height()));
+        assert!(bounds.contains(&Point::new(3, 4)));
+        assert!(!bounds.contains(&Point::new(4, 4)));
+        assert_eq!(None, BoundingBox::<i64>::from_points(vec![]));
+        assert_eq!(
+            BoundingBox { min: Point::new(-2, -1), max: Point::new(5, 5) },
+            bounds.union(&BoundingBox::new(Point::new(5, 5), Point::new(4, 4)))
+        );
+    }
+
+    #[test]
+    fn test_segment_intersections() {
+        let segments = vec![
+            Segment::new(Point::new(0, 0), Point::new(10, 0)).unwrap(),
+            Segment::new(Point::new(5, -5), Point::new(5, 5)).unwrap(),
+            Segment::new(Point::new(8, 0), Point::new(20, 0)).unwrap(),
+            Segment::new(Point::new(5, 3), Point::new(5, 9)).unwrap(),
+            Segment::new(Point::new(-1, 9), Point::new(1, 9)).unwrap(),
+            Segment::new(Point::new(20, 0), Point::new(20, 0)).unwrap(),
+        ];
+
+        let expected = vec![
+            (0, 1, Segment { start: Point::new(5, 0), end: Point::new(5, 0) }),
+            (0, 2, Segment { start: Point::new(8, 0), end: Point::new(10, 0) }),
+            (1, 3, Segment { start: Point::new(5, 3), end: Point::new(5, 5) }),
+            (2, 5, Segment { start: Point::new(20, 0), end: Point::new(20, 0) }),
+        ];
+        assert_eq!(expected, segment_intersections(&segments));
+    }
+
+    #[test]
+    fn test_segment_intersections_agree_with_every_pair() {
+        // A random walk in a small area, so there are plenty of crossings and
+        // overlaps.
+        let mut seed: u64 = 2019;
+        let mut next = |range: i64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as i64 % range
+        };
+        let mut point = Point::origin();
+        let mut segments = vec![];
+        for _ in 0..400 {
+            let end = point + Direction8::all()[next(8) as usize].vector() * next(12);
+            segments.push(Segment::new(point, end).unwrap());
+            point = end;
+        }
+
+        let mut expected = vec![];
+        for a in 0..segments.len() {
+            for b in a + 1..segments.len() {
+                if let Some(overlap) = segments[a].intersection(&segments[b]) {
+                    expected.push((a, b, overlap));
+                }
+            }
+        }
+        assert_eq!(expected, segment_intersections(&segments));
+    }
+
+    #[test]
+    fn test_segment_intersections_with_one_diagonal() {
+        // A single diagonal mustn't make every segment look at every other.
+        let horizontals: Vec<Segment> = (0..20000)
+            .map(|y| Segment::new(Point::new(0, y), Point::new(10, y)).unwrap())
+            .collect();
+
+        for (from, to, crossed) in [(-5, 5, 0..=5), (5, 15, 5..=10)] {
+            let mut segments = horizontals.clone();
+            segments.push(Segment::new(Point::new(from, from), Point::new(to, to)).unwrap());
+
+            let expected: Vec<(usize, usize, Segment)> = crossed
+                .map(|y| (y as usize, 20000, Segment { start: Point::new(y, y), end: Point::new(y, y) }))
+                .collect();
+            assert_eq!(expected, segment_intersections(&segments));
+            // Whether the diagonal starts before or after them, each
+            // horizontal segment is only looked at twice: once to tidy away,
+            // once to check.
+            assert_eq!(2 * 20000, diagonal_pairs(&segments).1);
+        }
+    }
+}
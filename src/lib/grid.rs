@@ -0,0 +1,343 @@
+//! A rectangular 2D grid, stored row by row. `x` grows to the right and `y`
+//! downwards, starting from the top left corner at `(0, 0)`.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use ansi_term::Colour;
+
+use parse::ParseError;
+
+pub use geometry::Point;
+
+const NEIGHBOURS_4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOURS_8: [(i64, i64); 8] = [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+}
+
+impl Grid<char> {
+    /// A grid of the characters of each non-empty line.
+    pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+        Grid::parse_with(input, Some)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from cells listed row by row.
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Option<Grid<T>> {
+        if cells.len() != width * height {
+            return None;
+        }
+        Some(Grid { width, height, cells })
+    }
+
+    /// A grid of each non-empty line, converting characters with `cell`.
+    /// Every line must be as long as the first, and `cell` must accept every
+    /// character.
+    pub fn parse_with<F: Fn(char) -> Option<T>>(input: &str, cell: F) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        let mut offset = 0;
+
+        for line in input.split('\n') {
+            let line_offset = offset;
+            offset += line.len() + 1;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            let line_width = line.chars().count();
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    return Err(ParseError::at(input, line_offset, line, &format!("a line of {} cells", width)));
+                }
+                _ => {}
+            }
+            for (index, c) in line.char_indices() {
+                let parsed = cell(c).ok_or_else(|| ParseError::at(input, line_offset + index, &c.to_string(), "a grid cell"))?;
+                cells.push(parsed);
+            }
+            height += 1;
+        }
+
+        Ok(Grid { width: width.unwrap_or(0), height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= 0 && point.y >= 0 && (point.x as usize) < self.width && (point.y as usize) < self.height
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(move |index| &mut self.cells[index])
+    }
+
+    /// Every point in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x as i64, y as i64)))
+    }
+
+    /// Every cell along with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The up to 4 points above, right, below and left of `point` that are
+    /// inside the grid.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &NEIGHBOURS_4)
+    }
+
+    /// The up to 8 points around `point`, diagonals included, that are inside
+    /// the grid.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &NEIGHBOURS_8)
+    }
+
+    fn neighbours<'a>(&'a self, point: Point, offsets: &'static [(i64, i64)]) -> impl Iterator<Item = Point> + 'a {
+        offsets.iter()
+            .map(move |&(dx, dy)| Point::new(point.x + dx, point.y + dy))
+            .filter(move |&neighbour| self.contains(neighbour))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} is outside a grid {} wide", x, self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Builds a grid of the given size from the cell each point should take
+    /// from this one.
+    fn rearranged<F: Fn(usize, usize) -> Point>(&self, width: usize, height: usize, source: F) -> Grid<T> where T: Clone {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self[source(x, y)].clone())
+            .collect();
+        Grid { width, height, cells }
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Grid<T> where T: Clone {
+        self.rearranged(self.height, self.width, |x, y| Point::new(y as i64, x as i64))
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T> where T: Clone {
+        let height = self.height;
+        self.rearranged(self.height, self.width, |x, y| Point::new(y as i64, (height - 1 - x) as i64))
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Grid<T> where T: Clone {
+        let width = self.width;
+        self.rearranged(self.height, self.width, |x, y| Point::new((width - 1 - y) as i64, x as i64))
+    }
+
+    /// The grid as lines of text, drawing each cell with `cell`.
+    pub fn render<F: Fn(&T) -> char>(&self, cell: F) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&cell).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Like `render`, with each cell also given a colour.
+    pub fn render_ansi<F: Fn(&T) -> (char, Colour)>(&self, cell: F) -> String {
+        self.rows()
+            .map(|row| row.iter().map(|value| {
+                let (c, colour) = cell(value);
+                colour.paint(c.to_string()).to_string()
+            }).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(point).unwrap_or_else(|| panic!("Point {:?} is outside a {}x{} grid", point, width, height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(point).unwrap_or_else(|| panic!("Point {:?} is outside a {}x{} grid", point, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = "abc\ndef\n";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(GRID).unwrap();
+
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!('f', grid[Point::new(2, 1)]);
+        assert_eq!(GRID.trim_end(), grid.to_string());
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let grid = Grid::parse_with("#.\r\n.#\r\n", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).unwrap();
+
+        assert_eq!(&[true, false], grid.row(0));
+        assert_eq!(
+            "Expected a grid cell at line 2, column 2, got '?'",
+            Grid::parse_with("#.\n.?\n", |c| if c == '?' { None } else { Some(c) }).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let error = Grid::parse("abc\nde\n").unwrap_err();
+
+        assert_eq!("Expected a line of 3 cells at line 2, column 1, got 'de'", error.to_string());
+    }
+
+    #[test]
+    fn test_get() {
+        let mut grid = Grid::new(2, 2, 0);
+        grid[Point::new(1, 0)] = 5;
+
+        assert_eq!(Some(&5), grid.get(Point::new(1, 0)));
+        assert_eq!(None, grid.get(Point::new(2, 0)));
+        assert_eq!(None, grid.get(Point::new(0, -1)));
+        assert!(grid.get_mut(Point::new(-1, 1)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Point Point { x: 3, y: 0 } is outside a 2x2 grid")]
+    fn test_index_out_of_bounds() {
+        let grid = Grid::new(2, 2, 0);
+        let _ = grid[Point::new(3, 0)];
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse(GRID).unwrap();
+
+        assert_eq!(vec![Point::new(1, 0), Point::new(0, 1)], grid.neighbours4(Point::new(0, 0)).collect::<Vec<Point>>());
+        assert_eq!(3, grid.neighbours4(Point::new(1, 0)).count());
+        assert_eq!(5, grid.neighbours8(Point::new(1, 1)).count());
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse(GRID).unwrap();
+
+        assert_eq!(vec![&['a', 'b', 'c'][..], &['d', 'e', 'f'][..]], grid.rows().collect::<Vec<&[char]>>());
+        assert_eq!(vec!['b', 'e'], grid.column(1).cloned().collect::<Vec<char>>());
+        assert_eq!(3, grid.columns().count());
+    }
+
+    #[test]
+    fn test_iter() {
+        let grid = Grid::parse(GRID).unwrap();
+
+        assert_eq!(Some((Point::new(0, 1), &'d')), grid.iter().nth(3));
+        assert_eq!(6, grid.points().count());
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let grid = Grid::parse(GRID).unwrap();
+
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!("da\neb\nfc", grid.rotate_clockwise().to_string());
+        assert_eq!("cf\nbe\nad", grid.rotate_counter_clockwise().to_string());
+        assert_eq!(grid, grid.rotate_clockwise().rotate_counter_clockwise());
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = Grid::from_cells(2, 2, vec![0, 1, 1, 0]).unwrap();
+
+        assert_eq!(" #\n# ", grid.render(|&cell| if cell == 1 { '#' } else { ' ' }));
+        assert_eq!(
+            format!("{}{}", Colour::Red.paint("0"), Colour::Green.paint("1")),
+            grid.render_ansi(|&cell| (if cell == 1 { '1' } else { '0' }, if cell == 1 { Colour::Green } else { Colour::Red }))
+                .lines().next().unwrap()
+        );
+        assert!(Grid::from_cells(2, 2, vec![0]).is_none());
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = Grid::parse(GRID).unwrap().map(|c| c.to_ascii_uppercase());
+
+        assert_eq!("ABC\nDEF", grid.to_string());
+    }
+}
@@ -0,0 +1,417 @@
+//! Puzzle inputs, read from a local directory and fetched on first use.
+//!
+//! `dayN.txt` in the inputs directory always wins. When it's missing and a
+//! session token is available, the input is downloaded from
+//! `<base url>/day/N/input`, saved as `dayN.txt` next to a `dayN.meta` file
+//! saying where and when it came from, and read from there from then on. An
+//! input is never fetched twice: if `dayN.meta` is there but `dayN.txt` has
+//! gone, that's an error rather than another download.
+//!
+//! Successful fetches are spaced at least `rate_limit` apart, tracked through
+//! a `.last-fetch` file in the inputs directory so that separate runs respect it
+//! too. Plain `http://` URLs are fetched directly, but only from loopback
+//! hosts, which is what tests use with a local server, so the session never
+//! goes out unencrypted; `https://` ones go through `curl`, with the session
+//! passed on its standard input rather than its command line.
+//!
+//! The `.session` file holds a secret: the repository ignores `.session` and
+//! `.last-fetch` files wherever they are, but an inputs directory elsewhere
+//! needs its own care.
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use read_inputs;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2019";
+pub const DEFAULT_RATE_LIMIT: Duration = Duration::from_secs(5);
+
+const USER_AGENT: &str = "advent_of_code_2019 input manager";
+const LAST_FETCH_FILE: &str = ".last-fetch";
+const SESSION_FILE: &str = ".session";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remote {
+    pub base_url: String,
+    pub session: String,
+}
+
+#[derive(Debug)]
+pub struct InputManager {
+    directory: PathBuf,
+    remote: Option<Remote>,
+    rate_limit: Duration,
+}
+
+impl InputManager {
+    /// A manager that only reads what's already in `directory`.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> InputManager {
+        InputManager {
+            directory: directory.into(),
+            remote: None,
+            rate_limit: DEFAULT_RATE_LIMIT,
+        }
+    }
+
+    /// A manager that fetches missing inputs when a session token is set,
+    /// either in `AOC_SESSION` or in a `.session` file in `directory`. The
+    /// base URL can be changed with `AOC_BASE_URL`.
+    pub fn from_env<P: Into<PathBuf>>(directory: P) -> InputManager {
+        let manager = InputManager::new(directory);
+        let session = env::var("AOC_SESSION").ok()
+            .or_else(|| fs::read_to_string(manager.directory.join(SESSION_FILE)).ok())
+            .map(|session| session.trim().to_string())
+            .filter(|session| !session.is_empty());
+
+        match session {
+            Some(session) => {
+                let base_url = env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+                manager.with_remote(&base_url, &session)
+            }
+            None => manager,
+        }
+    }
+
+    pub fn with_remote(mut self, base_url: &str, session: &str) -> InputManager {
+        self.remote = Some(Remote {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+        });
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: Duration) -> InputManager {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        self.directory.join(format!("day{}.txt", day))
+    }
+
+    pub fn metadata_path(&self, day: u32) -> PathBuf {
+        self.directory.join(format!("day{}.meta", day))
+    }
+
+    /// The input for `day`, fetching and caching it if it isn't there yet.
+    pub fn input(&self, day: u32) -> Result<String, String> {
+        let path = self.input_path(day);
+        if path.exists() {
+            return read_inputs(&path.to_string_lossy());
+        }
+        let metadata = self.metadata_path(day);
+        if metadata.exists() {
+            return Err(format!("Couldn't read {}: it was already fetched, as {} says, but has since been removed",
+                path.display(), metadata.display()));
+        }
+
+        let remote = self.remote.as_ref().ok_or_else(|| format!(
+            "Couldn't read {}: it doesn't exist, and there's no AOC_SESSION to fetch it with", path.display()))?;
+        let url = format!("{}/day/{}/input", remote.base_url, day);
+
+        self.wait_for_rate_limit();
+        let input = fetch(&url, &remote.session)?;
+        self.write(&self.directory.join(LAST_FETCH_FILE), &unix_time().to_string())?;
+
+        self.write(&path, &input)?;
+        self.write(&self.metadata_path(day), &format!("url = {}\nfetched_at_ms = {}\nbytes = {}\n", url, unix_time(), input.len()))?;
+        Ok(input)
+    }
+
+    fn wait_for_rate_limit(&self) {
+        let last_fetch = fs::read_to_string(self.directory.join(LAST_FETCH_FILE)).ok()
+            .and_then(|millis| millis.trim().parse::<u64>().ok());
+
+        if let Some(last_fetch) = last_fetch {
+            // Both times are truncated to the millisecond, so up to one more
+            // may have passed than it seems.
+            let since = Duration::from_millis(unix_time().saturating_sub(last_fetch));
+            if since < self.rate_limit {
+                thread::sleep(self.rate_limit - since + Duration::from_millis(1));
+            }
+        }
+    }
+
+    fn write(&self, path: &PathBuf, contents: &str) -> Result<(), String> {
+        fs::create_dir_all(&self.directory)
+            .and_then(|_| fs::write(path, contents))
+            .map_err(|error| format!("Couldn't write {}: {}", path.display(), error))
+    }
+}
+
+// Milliseconds since the epoch.
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_millis() as u64).unwrap_or(0)
+}
+
+/// Fetches `url` with the session cookie set, returning the body of a `200`
+/// response.
+pub fn fetch(url: &str, session: &str) -> Result<String, String> {
+    if url.starts_with("https://") {
+        return fetch_with_curl(url, session);
+    }
+    let rest = url.strip_prefix("http://").ok_or(format!("Unsupported URL {}", url))?;
+    let (host, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    if !is_loopback(host) {
+        return Err(format!("Refusing to send the session over plain HTTP to {}, use https://", host));
+    }
+    let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+
+    let mut stream = TcpStream::connect(&address).map_err(|error| format!("Couldn't connect to {}: {}", host, error))?;
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\nCookie: session={}\r\nUser-Agent: {}\r\nConnection: close\r\n\r\n",
+        path, host, session, USER_AGENT)
+        .map_err(|error| format!("Couldn't send request to {}: {}", host, error))?;
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response).map_err(|error| format!("Couldn't read response from {}: {}", host, error))?;
+    parse_response(&response).map_err(|error| format!("Fetching {} failed: {}", url, error))
+}
+
+// Whether `host`, with or without a port, is this machine.
+fn is_loopback(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    name == "localhost" || name.parse::<IpAddr>().is_ok_and(|address| address.is_loopback())
+}
+
+// A curl config line setting the session cookie, so that the session doesn't
+// show up in the process list.
+fn curl_cookie_config(session: &str) -> String {
+    format!("cookie = \"session={}\"\n", session.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn fetch_with_curl(url: &str, session: &str) -> Result<String, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--user-agent", USER_AGENT, "--config", "-"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("Couldn't run curl to fetch {}: {}", url, error))?;
+    child.stdin.take().expect("curl's stdin is piped")
+        .write_all(curl_cookie_config(session).as_bytes())
+        .map_err(|error| format!("Couldn't pass the session to curl: {}", error))?;
+    let output = child.wait_with_output().map_err(|error| format!("Couldn't run curl to fetch {}: {}", url, error))?;
+
+    if !output.status.success() {
+        return Err(format!("Fetching {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|_| format!("Fetching {} failed: the response isn't UTF-8", url))
+}
+
+fn parse_response(response: &[u8]) -> Result<String, String> {
+    let split = response.windows(4).position(|window| window == b"\r\n\r\n").ok_or("malformed response")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let body = &response[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines.next().and_then(|line| line.split_whitespace().nth(1)).ok_or("malformed status line")?;
+    if status != "200" {
+        let hint = if status == "400" || status == "401" || status == "404" { " (is the session token still valid?)" } else { "" };
+        return Err(format!("the server responded with status {}{}", status, hint));
+    }
+
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    let body = if chunked { dechunk(body)? } else { body.to_vec() };
+
+    String::from_utf8(body).map_err(|_| "the response isn't UTF-8".to_string())
+}
+
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = vec![];
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n").ok_or("malformed chunk")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| "malformed chunk size")?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let start = line_end + 2;
+        if body.len() < start + size + 2 {
+            return Err("truncated chunk".to_string());
+        }
+        decoded.extend_from_slice(&body[start..start + size]);
+        body = &body[start + size + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    // A stand-in for the puzzle server. It answers `/day/N/input` with a made
+    // up input when the session is "secret", and keeps the request lines.
+    fn serve() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/2019", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = vec![];
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push(line.trim().to_string());
+                }
+                let authorised = request.iter().any(|line| line == "Cookie: session=secret");
+                let day = request[0].split('/').nth(3).unwrap_or("").to_string();
+                seen.lock().unwrap().push(request[0].clone());
+
+                let response = if !authorised {
+                    "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else if day == "2" {
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n1,2,\r\n3\r\n3,4\r\n0\r\n\r\n".to_string()
+                } else {
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\ninput for {}\n", day)
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    fn scratch_directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("advent-inputs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn test_input_reads_existing_file() {
+        let manager = InputManager::new("inputs").with_remote("http://127.0.0.1:9", "secret");
+
+        assert_eq!(read_inputs("inputs/day1.txt"), manager.input(1));
+    }
+
+    #[test]
+    fn test_input_without_session() {
+        let directory = scratch_directory("no-session");
+        let error = InputManager::new(&directory).input(1).unwrap_err();
+
+        assert!(error.contains("there's no AOC_SESSION"));
+    }
+
+    #[test]
+    fn test_input_is_fetched_once_and_cached() {
+        let (base_url, requests) = serve();
+        let directory = scratch_directory("cached");
+        let manager = InputManager::new(&directory).with_remote(&base_url, "secret").with_rate_limit(Duration::from_millis(0));
+
+        assert_eq!(Ok("input for 1\n".to_string()), manager.input(1));
+        assert_eq!(Ok("input for 1\n".to_string()), manager.input(1));
+        assert_eq!(vec!["GET /2019/day/1/input HTTP/1.1".to_string()], *requests.lock().unwrap());
+
+        let metadata = fs::read_to_string(manager.metadata_path(1)).unwrap();
+        assert!(metadata.starts_with(&format!("url = {}/day/1/input\nfetched_at_ms = ", base_url)));
+        assert!(metadata.ends_with("bytes = 12\n"));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_input_chunked_response() {
+        let (base_url, _) = serve();
+        let directory = scratch_directory("chunked");
+        let manager = InputManager::new(&directory).with_remote(&base_url, "secret").with_rate_limit(Duration::from_millis(0));
+
+        assert_eq!(Ok("1,2,3,4".to_string()), manager.input(2));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_input_rejected_session() {
+        let (base_url, _) = serve();
+        let directory = scratch_directory("rejected");
+        let manager = InputManager::new(&directory).with_remote(&base_url, "expired").with_rate_limit(Duration::from_millis(0));
+
+        let error = manager.input(3).unwrap_err();
+        assert!(error.contains("status 400 (is the session token still valid?)"));
+        assert!(!manager.input_path(3).exists());
+        assert!(!directory.join(LAST_FETCH_FILE).exists());
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_input_is_not_fetched_again_once_removed() {
+        let (base_url, requests) = serve();
+        let directory = scratch_directory("removed");
+        let manager = InputManager::new(&directory).with_remote(&base_url, "secret").with_rate_limit(Duration::from_millis(0));
+
+        manager.input(6).unwrap();
+        fs::remove_file(manager.input_path(6)).unwrap();
+
+        let error = manager.input(6).unwrap_err();
+        assert!(error.contains("it was already fetched"));
+        assert_eq!(1, requests.lock().unwrap().len());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_input_respects_rate_limit() {
+        let (base_url, requests) = serve();
+        let directory = scratch_directory("rate-limit");
+        let manager = InputManager::new(&directory).with_remote(&base_url, "secret").with_rate_limit(Duration::from_millis(300));
+
+        let start = Instant::now();
+        manager.input(4).unwrap();
+        manager.input(5).unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(2, requests.lock().unwrap().len());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_refuses_plain_http_elsewhere() {
+        let error = fetch("http://adventofcode.com/2019/day/1/input", "secret").unwrap_err();
+
+        assert!(error.starts_with("Refusing to send the session over plain HTTP to adventofcode.com"));
+        assert!(is_loopback("127.0.0.1:8080"));
+        assert!(is_loopback("localhost"));
+        assert!(is_loopback("[::1]:8080"));
+        assert!(!is_loopback("10.0.0.1:80"));
+        assert!(!is_loopback("localhost.example.com"));
+    }
+
+    #[test]
+    fn test_curl_cookie_config() {
+        assert_eq!("cookie = \"session=53616c7465\"\n", curl_cookie_config("53616c7465"));
+        assert_eq!("cookie = \"session=a\\\"b\\\\c\"\n", curl_cookie_config("a\"b\\c"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(Ok("abc".to_string()), parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nabc"));
+        assert!(parse_response(b"HTTP/1.1 500 Internal Server Error\r\n\r\n").is_err());
+        assert!(parse_response(b"garbage").is_err());
+    }
+}
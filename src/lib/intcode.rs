@@ -1,31 +1,108 @@
+use std::fmt;
+
+pub mod disassembler;
+pub mod heatmap;
+pub mod optimiser;
+pub mod record;
+
 pub fn run_program(memory: &Vec<i64>, input: &i64) -> ProgramResult {
+    run_program_with_decoding(memory, input, Decoding::Lenient).expect("Lenient decoding never fails")
+}
+
+pub fn run_program_with_decoding(memory: &[i64], input: &i64, decoding: Decoding) -> Result<ProgramResult, DecodeError> {
+    run(memory, input, decoding, None::<fn(&Step)>)
+}
+
+/// Runs a program, calling `observe` after every executed instruction.
+pub fn run_program_observed<F: FnMut(&Step)>(memory: &[i64], input: &i64, decoding: Decoding, observe: F) -> Result<ProgramResult, DecodeError> {
+    run(memory, input, decoding, Some(observe))
+}
+
+// Only builds a `Step` when there's something to observe it.
+fn run<F: FnMut(&Step)>(memory: &[i64], input: &i64, decoding: Decoding, mut observe: Option<F>) -> Result<ProgramResult, DecodeError> {
     let mut instruction_pointer = 0;
-    let mut program_result = ProgramResult{memory: memory.clone(), output: vec![]};
+    let mut program_result = ProgramResult{memory: memory.to_vec(), output: vec![]};
 
-    while get_operation(&program_result.memory, &instruction_pointer) != Operation::Halt {
+    loop {
+        if decoding == Decoding::Strict {
+            validate_instruction(&program_result.memory, &instruction_pointer)?;
+        }
+        if get_operation(&program_result.memory, &instruction_pointer) == Operation::Halt {
+            break;
+        }
         // println!("Memory: {:?} | PC: {}", program_result.memory, instruction_pointer);
+        let observed = observe.as_mut().map(|observe| {
+            let length = get_operation_length(&get_operation(&program_result.memory, &instruction_pointer));
+            (observe, length, get_read_addresses(&program_result.memory, &instruction_pointer))
+        });
         let instruction_result = run_instruction(&program_result.memory, &instruction_pointer, input);
         program_result.memory = instruction_result.memory;
         program_result.output.extend(instruction_result.output);
-        instruction_pointer = instruction_result.instruction_pointer.unwrap_or(instruction_pointer + get_operation_length(&get_operation(&program_result.memory, &instruction_pointer)));
+        let next_instruction_pointer = instruction_result.instruction_pointer.unwrap_or(instruction_pointer + get_operation_length(&get_operation(&program_result.memory, &instruction_pointer)));
+
+        if let Some((observe, length, reads)) = observed {
+            observe(&Step{
+                instruction_pointer,
+                next_instruction_pointer,
+                length,
+                reads,
+                write: instruction_result.write,
+                output: instruction_result.output,
+            });
+        }
+        instruction_pointer = next_instruction_pointer;
+    }
+
+    Ok(program_result)
+}
+
+pub fn validate_instruction(memory: &[i64], instruction_pointer: &usize) -> Result<(), DecodeError> {
+    let raw = *memory.get(*instruction_pointer).expect("Couldn't get operation");
+    let error = |kind| Err(DecodeError{address: *instruction_pointer, raw, kind});
+
+    let operation = match to_operation(&raw) {
+        Some(operation) => operation,
+        None => return error(DecodeErrorKind::UnknownOperation),
+    };
+    let parameters = get_operation_length(&operation) - 1;
+    let write_parameter = match operation {
+        Operation::Add | Operation::Multiply | Operation::LessThan | Operation::Equals => Some(3),
+        Operation::Input => Some(1),
+        _ => None
+    };
+
+    for parameter in 1..=parameters {
+        let digit = std::char::from_digit(((raw / 10_i64.pow(parameter as u32 + 1)) % 10) as u32, 10).unwrap();
+        match to_mode(&digit) {
+            None => return error(DecodeErrorKind::UnknownMode{parameter, digit}),
+            Some(Mode::Immediate) if write_parameter == Some(parameter) => return error(DecodeErrorKind::ImmediateWrite{parameter}),
+            _ => {}
+        }
     }
 
-    program_result
+    if raw / 10_i64.pow(parameters as u32 + 2) != 0 {
+        return error(DecodeErrorKind::ExtraDigits);
+    }
+    Ok(())
 }
 
 fn get_operation(memory: &Vec<i64>, instruction_pointer: &usize) -> Operation {
     let operation = memory.get(*instruction_pointer).expect("Couldn't get operation");
+    to_operation(operation).expect("Unknwon operation")
+}
+
+fn to_operation(operation: &i64) -> Option<Operation> {
     match *operation % 100 {
-        1 => Operation::Add,
-        2 => Operation::Multiply,
-        3 => Operation::Input,
-        4 => Operation::Output,
-        5 => Operation::JumpIfTrue,
-        6 => Operation::JumpIfFalse,
-        7 => Operation::LessThan,
-        8 => Operation::Equals,
-        99 => Operation::Halt,
-        _ => panic!("Unknwon operation")
+        1 => Some(Operation::Add),
+        2 => Some(Operation::Multiply),
+        3 => Some(Operation::Input),
+        4 => Some(Operation::Output),
+        5 => Some(Operation::JumpIfTrue),
+        6 => Some(Operation::JumpIfFalse),
+        7 => Some(Operation::LessThan),
+        8 => Some(Operation::Equals),
+        99 => Some(Operation::Halt),
+        _ => None
     }
 }
 
@@ -74,6 +151,22 @@ fn get_parameters(memory: &Vec<i64>, instruction_pointer: &usize) -> Vec<i64> {
     }).collect()
 }
 
+fn get_read_addresses(memory: &Vec<i64>, instruction_pointer: &usize) -> Vec<usize> {
+    let inputs = match get_operation(memory, instruction_pointer) {
+        Operation::Add | Operation::Multiply | Operation::LessThan | Operation::Equals => 2,
+        Operation::JumpIfTrue | Operation::JumpIfFalse => 2,
+        Operation::Output => 1,
+        Operation::Input | Operation::Halt => 0,
+    };
+
+    get_modes(memory, instruction_pointer).iter()
+    .take(inputs)
+    .enumerate()
+    .filter(|(_, mode)| **mode == Mode::Position)
+    .map(|(i, _)| *memory.get(instruction_pointer + 1 + i).expect("Couldnt get parameter") as usize)
+    .collect()
+}
+
 fn get_result_address(memory: &Vec<i64>, instruction_pointer: &usize) -> Option<usize> {
     let operation_length = get_operation_length(&get_operation(memory, instruction_pointer));
     if operation_length == 1 { 
@@ -92,16 +185,19 @@ fn run_instruction(memory: &Vec<i64>, instruction_pointer: &usize, input: &i64)
     let result = run_operation(&operation, &parameters, input);
     // println!("Memory: {:?} | Operation: {:?} | Parameters: {:?} | Input: {} | Result: {:?}", memory, operation, parameters, input, result);
 
+    let mut write = None;
     if let Some(new_result) = result.result {
         let index_result = get_result_address(memory, instruction_pointer);
         let result_memory = new_memory.get_mut(index_result.unwrap()).expect("Couldn't get result element");
+        write = Some(MemoryWrite{address: index_result.unwrap(), old: *result_memory, new: new_result});
         *result_memory = new_result;
     }
 
     InstructionResult {
         memory: new_memory,
         output: result.output,
-        instruction_pointer: result.instruction_pointer
+        instruction_pointer: result.instruction_pointer,
+        write
     }
 }
 
@@ -119,14 +215,14 @@ fn run_operation(operation: &Operation, parameters: &Vec<i64>, input: &i64) -> O
     };
 }
 
-#[derive(Debug, PartialEq)]
-enum Mode {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
     Position,
     Immediate,
 }
 
-#[derive(Debug, PartialEq)]
-enum Operation {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
     Add,
     Multiply,
     Input,
@@ -178,6 +274,7 @@ struct InstructionResult {
     memory: Vec<i64>,
     output: Option<i64>,
     instruction_pointer: Option<usize>,
+    write: Option<MemoryWrite>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -185,6 +282,63 @@ pub struct ProgramResult {
     pub memory: Vec<i64>,
     pub output: Vec<i64>
 }
+
+/// A single executed instruction: where it was, where execution continued, and
+/// its effects. `reads` holds the addresses of position mode operands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub instruction_pointer: usize,
+    pub next_instruction_pointer: usize,
+    pub length: usize,
+    pub reads: Vec<usize>,
+    pub write: Option<MemoryWrite>,
+    pub output: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryWrite {
+    pub address: usize,
+    pub old: i64,
+    pub new: i64,
+}
+
+/// How instruction encodings are checked before they are executed.
+///
+/// `Lenient` accepts anything the interpreter can make sense of: unknown mode
+/// digits are ignored, as are digits beyond the last parameter. `Strict`
+/// rejects them, as well as write parameters in immediate mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decoding {
+    Lenient,
+    Strict,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodeError {
+    pub address: usize,
+    pub raw: i64,
+    pub kind: DecodeErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeErrorKind {
+    UnknownOperation,
+    UnknownMode { parameter: usize, digit: char },
+    ImmediateWrite { parameter: usize },
+    ExtraDigits,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid instruction {} at address {}: ", self.raw, self.address)?;
+        match self.kind {
+            DecodeErrorKind::UnknownOperation => write!(f, "unknown operation {}", self.raw % 100),
+            DecodeErrorKind::UnknownMode{parameter, digit} => write!(f, "unknown mode '{}' for parameter {}", digit, parameter),
+            DecodeErrorKind::ImmediateWrite{parameter} => write!(f, "parameter {} is written to but is in immediate mode", parameter),
+            DecodeErrorKind::ExtraDigits => write!(f, "unexpected digits before the parameter modes"),
+        }
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +507,79 @@ mod tests {
         assert_eq!(1, get_operation_length(&Operation::Halt));
     }
 
+    #[test]
+    fn run_program_lenient_ignores_unknown_modes() {
+        let initial_memory = vec![30001, 5, 6, 7, 99, 1, 2, 999];
+        let expected_memory = vec![30001, 5, 6, 7, 99, 1, 2, 3];
+
+        assert_eq!(expected_memory, run_program_with_decoding(&initial_memory, &1, Decoding::Lenient).unwrap().memory);
+    }
+
+    #[test]
+    fn run_program_strict() {
+        let initial_memory = vec![1002,4,3,4,33];
+        let expected_memory = vec![1002,4,3,4,99];
+
+        assert_eq!(expected_memory, run_program_with_decoding(&initial_memory, &1, Decoding::Strict).unwrap().memory);
+    }
+
+    #[test]
+    fn run_program_strict_reports_address_of_invalid_instruction() {
+        let initial_memory = vec![1101, 2, 3, 8, 30001, 5, 6, 7, 99];
+        let expected_error = DecodeError{address: 4, raw: 30001, kind: DecodeErrorKind::UnknownMode{parameter: 3, digit: '3'}};
+
+        assert_eq!(Err(expected_error), run_program_with_decoding(&initial_memory, &1, Decoding::Strict));
+    }
+
+    #[test]
+    fn test_validate_instruction() {
+        assert_eq!(Ok(()), validate_instruction(&[1101, 1, 2, 3], &0));
+        assert_eq!(Ok(()), validate_instruction(&[104, 1], &0));
+        assert_eq!(Ok(()), validate_instruction(&[99], &0));
+        assert_eq!(DecodeErrorKind::UnknownOperation, validate_instruction(&[42], &0).unwrap_err().kind);
+        assert_eq!(DecodeErrorKind::UnknownMode{parameter: 1, digit: '2'}, validate_instruction(&[201, 1, 2, 3], &0).unwrap_err().kind);
+        assert_eq!(DecodeErrorKind::ImmediateWrite{parameter: 3}, validate_instruction(&[10001, 1, 2, 3], &0).unwrap_err().kind);
+        assert_eq!(DecodeErrorKind::ImmediateWrite{parameter: 1}, validate_instruction(&[103, 1], &0).unwrap_err().kind);
+        assert_eq!(DecodeErrorKind::ExtraDigits, validate_instruction(&[1104, 1], &0).unwrap_err().kind);
+        assert_eq!(DecodeErrorKind::ExtraDigits, validate_instruction(&[100099], &0).unwrap_err().kind);
+    }
+
+    #[test]
+    fn test_decode_error_display() {
+        let error = DecodeError{address: 223, raw: 30001, kind: DecodeErrorKind::UnknownMode{parameter: 3, digit: '3'}};
+        assert_eq!("Invalid instruction 30001 at address 223: unknown mode '3' for parameter 3", error.to_string());
+    }
+
+    #[test]
+    fn test_run_instruction_write() {
+        let initial_memory = vec![1, 5, 6, 7, 99, 1, 2, 999];
+
+        assert_eq!(Some(MemoryWrite{address: 7, old: 999, new: 3}), run_instruction(&initial_memory, &0, &1).write);
+        assert_eq!(None, run_instruction(&vec![4, 3, 99, 5], &0, &1).write);
+    }
+
+    #[test]
+    fn run_program_observed_reports_every_step() {
+        let initial_memory = vec![1101, 2, 3, 7, 4, 7, 99, 0];
+        let mut steps = vec![];
+        run_program_observed(&initial_memory, &1, Decoding::Lenient, |step| steps.push(step.clone())).unwrap();
+
+        let expected_steps = vec![
+            Step{instruction_pointer: 0, next_instruction_pointer: 4, length: 4, reads: vec![], write: Some(MemoryWrite{address: 7, old: 0, new: 5}), output: None},
+            Step{instruction_pointer: 4, next_instruction_pointer: 6, length: 2, reads: vec![7], write: None, output: Some(5)},
+        ];
+        assert_eq!(expected_steps, steps);
+    }
+
+    #[test]
+    fn test_get_read_addresses() {
+        assert_eq!(vec![5, 6], get_read_addresses(&vec![1, 5, 6, 7], &0));
+        assert_eq!(vec![5], get_read_addresses(&vec![1001, 5, 6, 7], &0));
+        assert_eq!(Vec::<usize>::new(), get_read_addresses(&vec![3, 5], &0));
+        assert_eq!(vec![5], get_read_addresses(&vec![4, 5], &0));
+        assert_eq!(vec![9], get_read_addresses(&vec![105, 1, 9], &0));
+    }
+
     #[test]
     fn test_get_result_address() {
         assert_eq!(Some(92), get_result_address(&vec![1, 90, 91, 92], &0));
@@ -0,0 +1,290 @@
+//! Static disassembly of Intcode images.
+//!
+//! Instructions are decoded strictly, following control flow from address 0.
+//! Anything that isn't reached that way is listed as data.
+
+use super::*;
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parameter {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub address: usize,
+    pub operation: Operation,
+    pub parameters: Vec<Parameter>,
+}
+
+impl Instruction {
+    pub fn length(&self) -> usize {
+        get_operation_length(&self.operation)
+    }
+
+    pub fn next_address(&self) -> usize {
+        self.address + self.length()
+    }
+
+    /// The parameters that are read as values, as opposed to the one that
+    /// names the address written to.
+    pub fn inputs(&self) -> &[Parameter] {
+        match self.write() {
+            Some(_) => &self.parameters[..self.parameters.len() - 1],
+            None => &self.parameters,
+        }
+    }
+
+    /// Addresses read through position mode parameters.
+    pub fn reads(&self) -> Vec<usize> {
+        self.inputs().iter().filter(|p| p.mode == Mode::Position).map(|p| p.value as usize).collect()
+    }
+
+    pub fn write(&self) -> Option<usize> {
+        match self.operation {
+            Operation::Add | Operation::Multiply | Operation::Input | Operation::LessThan | Operation::Equals =>
+                self.parameters.last().map(|p| p.value as usize),
+            _ => None,
+        }
+    }
+
+    pub fn is_jump(&self) -> bool {
+        self.operation == Operation::JumpIfTrue || self.operation == Operation::JumpIfFalse
+    }
+
+    /// Re-encodes the instruction into its memory cells.
+    pub fn encode(&self) -> Vec<i64> {
+        let opcode = match self.operation {
+            Operation::Add => 1,
+            Operation::Multiply => 2,
+            Operation::Input => 3,
+            Operation::Output => 4,
+            Operation::JumpIfTrue => 5,
+            Operation::JumpIfFalse => 6,
+            Operation::LessThan => 7,
+            Operation::Equals => 8,
+            Operation::Halt => 99,
+        };
+        let modes = self.parameters.iter().enumerate()
+            .filter(|(_, p)| p.mode == Mode::Immediate)
+            .map(|(i, _)| 10_i64.pow(i as u32 + 2))
+            .sum::<i64>();
+
+        let mut cells = vec![opcode + modes];
+        cells.extend(self.parameters.iter().map(|p| p.value));
+        cells
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mnemonic = match self.operation {
+            Operation::Add => "ADD",
+            Operation::Multiply => "MUL",
+            Operation::Input => "IN",
+            Operation::Output => "OUT",
+            Operation::JumpIfTrue => "JNZ",
+            Operation::JumpIfFalse => "JZ",
+            Operation::LessThan => "LT",
+            Operation::Equals => "EQ",
+            Operation::Halt => "HLT",
+        };
+        let parameters: Vec<String> = self.parameters.iter().map(|p| match p.mode {
+            Mode::Position => format!("[{}]", p.value),
+            Mode::Immediate => p.value.to_string(),
+        }).collect();
+
+        if parameters.is_empty() {
+            write!(f, "{}", mnemonic)
+        } else {
+            write!(f, "{:<4}{}", mnemonic, parameters.join(", "))
+        }
+    }
+}
+
+/// Strictly decodes the instruction at `address`, if there is a valid one.
+pub fn decode(memory: &[i64], address: usize) -> Option<Instruction> {
+    if address >= memory.len() || validate_instruction(memory, &address).is_err() {
+        return None;
+    }
+    let operation = to_operation(&memory[address])?;
+    let length = get_operation_length(&operation);
+    if address + length > memory.len() {
+        return None;
+    }
+
+    let parameters = (1..length).map(|parameter| {
+        let digit = (memory[address] / 10_i64.pow(parameter as u32 + 1)) % 10;
+        Parameter {
+            mode: if digit == 1 { Mode::Immediate } else { Mode::Position },
+            value: memory[address + parameter],
+        }
+    }).collect();
+
+    Some(Instruction { address, operation, parameters })
+}
+
+/// Where execution may continue after an instruction, as far as is known
+/// statically.
+#[derive(Debug, PartialEq)]
+pub enum Successors {
+    Known(Vec<usize>),
+    Unknown,
+}
+
+/// Successors of an instruction assuming nothing about the contents of memory:
+/// conditional jumps may go either way, and only jumps to an immediate
+/// address have a known destination.
+pub fn successors(instruction: &Instruction) -> Successors {
+    match instruction.operation {
+        Operation::Halt => Successors::Known(vec![]),
+        Operation::JumpIfTrue | Operation::JumpIfFalse => match instruction.parameters[1] {
+            Parameter { mode: Mode::Immediate, value } if value >= 0 =>
+                Successors::Known(vec![instruction.next_address(), value as usize]),
+            _ => Successors::Unknown,
+        },
+        _ => Successors::Known(vec![instruction.next_address()]),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Disassembly {
+    pub memory: Vec<i64>,
+    pub instructions: BTreeMap<usize, Instruction>,
+    /// Reachable addresses that either don't hold a valid instruction or lead
+    /// somewhere that can't be worked out statically.
+    pub unresolved: Vec<usize>,
+}
+
+impl Disassembly {
+    /// Whether every reachable instruction was decoded and has known
+    /// successors.
+    pub fn is_complete(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+
+    /// Whether `address` belongs to a reachable instruction.
+    pub fn is_code(&self, address: usize) -> bool {
+        self.instructions.range(..=address).next_back().is_some_and(|(_, i)| address < i.next_address())
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.memory.len().to_string().len();
+        let mut address = 0;
+        while address < self.memory.len() {
+            match self.instructions.get(&address) {
+                Some(instruction) => {
+                    writeln!(f, "{:>w$}: {}", address, instruction, w = width)?;
+                    address = instruction.next_address();
+                }
+                None => {
+                    writeln!(f, "{:>w$}: DATA {}", address, self.memory[address], w = width)?;
+                    address += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn disassemble(memory: &[i64]) -> Disassembly {
+    disassemble_with(memory, successors)
+}
+
+/// Disassembles following the control flow given by `successors`.
+pub fn disassemble_with<F: Fn(&Instruction) -> Successors>(memory: &[i64], successors: F) -> Disassembly {
+    let mut instructions = BTreeMap::new();
+    let mut unresolved = vec![];
+    let mut queue = vec![0];
+
+    while let Some(address) = queue.pop() {
+        if instructions.contains_key(&address) || unresolved.contains(&address) {
+            continue;
+        }
+        match decode(memory, address) {
+            Some(instruction) => {
+                match successors(&instruction) {
+                    Successors::Known(next) => queue.extend(next),
+                    Successors::Unknown => unresolved.push(address),
+                }
+                instructions.insert(address, instruction);
+            }
+            None => unresolved.push(address),
+        }
+    }
+    unresolved.sort();
+
+    Disassembly { memory: memory.to_vec(), instructions, unresolved }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs 999, 1000 or 1001 depending on whether the input is below, equal
+    // to or above 8.
+    const COMPARE_TO_EIGHT: [i64; 47] = [3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+
+    #[test]
+    fn test_decode() {
+        let instruction = decode(&[1002, 4, 3, 4, 33], 0).unwrap();
+
+        assert_eq!(Operation::Multiply, instruction.operation);
+        assert_eq!(vec![
+            Parameter { mode: Mode::Position, value: 4 },
+            Parameter { mode: Mode::Immediate, value: 3 },
+            Parameter { mode: Mode::Position, value: 4 },
+        ], instruction.parameters);
+        assert_eq!(vec![4], instruction.reads());
+        assert_eq!(Some(4), instruction.write());
+        assert_eq!(vec![1002, 4, 3, 4], instruction.encode());
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(None, decode(&[30001, 0, 0, 0], 0));
+        assert_eq!(None, decode(&[1, 0, 0], 0));
+        assert_eq!(None, decode(&[99], 1));
+    }
+
+    #[test]
+    fn test_instruction_display() {
+        assert_eq!("MUL [4], 3, [4]", decode(&[1002, 4, 3, 4], 0).unwrap().to_string());
+        assert_eq!("HLT", decode(&[99], 0).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let disassembly = disassemble(&COMPARE_TO_EIGHT);
+
+        // Unconditional jumps are still assumed to fall through.
+        assert_eq!(vec![19, 45], disassembly.unresolved);
+        assert_eq!(
+            vec![0, 2, 6, 9, 13, 16, 22, 26, 28, 31, 33, 36, 40, 42, 46],
+            disassembly.instructions.keys().cloned().collect::<Vec<usize>>()
+        );
+        assert!(disassembly.is_code(24));
+        assert!(!disassembly.is_code(19));
+    }
+
+    #[test]
+    fn test_disassemble_unresolved() {
+        // Jumps to the address held in 5.
+        let disassembly = disassemble(&[5, 4, 5, 99, 1, 3]);
+
+        assert_eq!(vec![0], disassembly.unresolved);
+        assert!(!disassembly.is_complete());
+    }
+
+    #[test]
+    fn test_disassembly_display() {
+        let disassembly = disassemble(&[1101, 2, 3, 5, 99, 0]);
+
+        assert_eq!("0: ADD 2, 3, [5]\n4: HLT\n5: DATA 0\n", disassembly.to_string());
+    }
+}
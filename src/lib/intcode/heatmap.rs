@@ -0,0 +1,229 @@
+//! Memory usage heatmaps for the Intcode VM.
+//!
+//! A run is observed step by step, counting per address how often it was
+//! executed as part of an instruction, read as a position mode operand and
+//! written. Addresses that were ever executed are code, the rest are data.
+//!
+//! Code is drawn in blue, brighter the more it ran, with red mixed in when it
+//! was also written (self-modifying code). Data is drawn with writes in red
+//! and reads in green, so cells that are both show as yellow.
+
+use super::*;
+
+use std::fmt::Write;
+
+use ansi_term::Colour::RGB;
+
+const UNTOUCHED: (u8, u8, u8) = (40, 40, 40);
+
+pub fn heatmap_program(memory: &[i64], input: &i64) -> Heatmap {
+    let mut heatmap = Heatmap {
+        executions: vec![0; memory.len()],
+        reads: vec![0; memory.len()],
+        writes: vec![0; memory.len()],
+    };
+
+    run_program_observed(memory, input, Decoding::Lenient, |step| heatmap.record(step))
+        .expect("Lenient decoding never fails");
+
+    heatmap
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Heatmap {
+    executions: Vec<usize>,
+    reads: Vec<usize>,
+    writes: Vec<usize>,
+}
+
+impl Heatmap {
+    fn record(&mut self, step: &Step) {
+        for address in step.instruction_pointer..step.instruction_pointer + step.length {
+            self.executions[address] += 1;
+        }
+        for address in &step.reads {
+            self.reads[*address] += 1;
+        }
+        if let Some(write) = step.write {
+            self.writes[write.address] += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.executions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.executions.is_empty()
+    }
+
+    pub fn executions(&self) -> &[usize] {
+        &self.executions
+    }
+
+    pub fn reads(&self) -> &[usize] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[usize] {
+        &self.writes
+    }
+
+    pub fn is_code(&self, address: usize) -> bool {
+        self.executions[address] > 0
+    }
+
+    pub fn colour(&self, address: usize) -> (u8, u8, u8) {
+        if self.is_code(address) {
+            let executions = intensity(self.executions[address], max(&self.executions));
+            let writes = intensity(self.writes[address], max(&self.writes));
+            (writes, 0, executions)
+        } else if self.reads[address] == 0 && self.writes[address] == 0 {
+            UNTOUCHED
+        } else {
+            let writes = intensity(self.writes[address], max(&self.writes));
+            let reads = intensity(self.reads[address], max(&self.reads));
+            (writes, reads, 0)
+        }
+    }
+
+    /// A binary PPM image with `width` addresses per row, each drawn as a
+    /// `cell_size` square. Addresses past the end of memory are black. Panics
+    /// if either is zero.
+    pub fn to_ppm(&self, width: usize, cell_size: usize) -> Vec<u8> {
+        assert!(width > 0 && cell_size > 0, "Expecting a non-zero width and cell size, got {} and {}", width, cell_size);
+        let rows = self.len().div_ceil(width);
+        let mut image = format!("P6\n{} {}\n255\n", width * cell_size, rows * cell_size).into_bytes();
+
+        for row in 0..rows {
+            for _ in 0..cell_size {
+                for column in 0..width {
+                    let address = row * width + column;
+                    let (r, g, b) = if address < self.len() { self.colour(address) } else { (0, 0, 0) };
+                    for _ in 0..cell_size {
+                        image.extend_from_slice(&[r, g, b]);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    /// A terminal view with `width` addresses per row, each row labelled with
+    /// its first address. Panics if `width` is zero.
+    pub fn to_ansi(&self, width: usize) -> String {
+        assert!(width > 0, "Expecting a non-zero width");
+        let label_width = self.len().to_string().len();
+        let mut view = String::new();
+
+        for start in (0..self.len()).step_by(width) {
+            write!(view, "{:>w$} ", start, w = label_width).unwrap();
+            for address in start..(start + width).min(self.len()) {
+                let (r, g, b) = self.colour(address);
+                write!(view, "{}", RGB(r, g, b).on(RGB(r, g, b)).paint("  ")).unwrap();
+            }
+            view.push('\n');
+        }
+
+        write!(view, "{} code  {} self-modified code  {} read  {} written  {} read and written",
+            RGB(0, 0, 255).on(RGB(0, 0, 255)).paint("  "),
+            RGB(255, 0, 255).on(RGB(255, 0, 255)).paint("  "),
+            RGB(0, 255, 0).on(RGB(0, 255, 0)).paint("  "),
+            RGB(255, 0, 0).on(RGB(255, 0, 0)).paint("  "),
+            RGB(255, 255, 0).on(RGB(255, 255, 0)).paint("  ")).unwrap();
+        view.push('\n');
+        view
+    }
+}
+
+fn max(counts: &[usize]) -> usize {
+    counts.iter().cloned().max().unwrap_or(0)
+}
+
+// Counts are log scaled, so that a few hot loops don't wash out everything
+// else. Anything touched at least once gets a visible colour.
+fn intensity(count: usize, max: usize) -> u8 {
+    if count == 0 {
+        return 0;
+    }
+    let scale = (count as f64).ln_1p() / (max as f64).ln_1p();
+    (55.0 + 200.0 * scale).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reads the input into 11, doubles it into 12 and outputs it.
+    const PROGRAM: [i64; 13] = [3, 11, 1002, 11, 2, 12, 4, 12, 99, 0, 0, 0, 0];
+
+    #[test]
+    fn test_heatmap_program() {
+        let heatmap = heatmap_program(&PROGRAM, &21);
+
+        assert_eq!(&[1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0], heatmap.executions());
+        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1], heatmap.reads());
+        assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1], heatmap.writes());
+        assert!(heatmap.is_code(0));
+        assert!(!heatmap.is_code(8));
+        assert!(!heatmap.is_code(11));
+    }
+
+    #[test]
+    fn test_heatmap_counts_loops() {
+        // Counts 11 down from 3 to 0.
+        let program = vec![1001, 11, -1, 11, 1005, 11, 0, 99, 0, 0, 0, 3];
+        let heatmap = heatmap_program(&program, &0);
+
+        assert_eq!(3, heatmap.executions()[0]);
+        assert_eq!(3, heatmap.executions()[4]);
+        assert_eq!(6, heatmap.reads()[11]);
+        assert_eq!(3, heatmap.writes()[11]);
+    }
+
+    #[test]
+    fn test_colour() {
+        let heatmap = heatmap_program(&PROGRAM, &21);
+
+        assert_eq!((0, 0, 255), heatmap.colour(0));
+        assert_eq!(UNTOUCHED, heatmap.colour(9));
+        assert_eq!((255, 255, 0), heatmap.colour(11));
+    }
+
+    #[test]
+    fn test_intensity() {
+        assert_eq!(0, intensity(0, 10));
+        assert_eq!(255, intensity(10, 10));
+        assert!(intensity(1, 10) < intensity(5, 10));
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let heatmap = heatmap_program(&PROGRAM, &21);
+        let image = heatmap.to_ppm(5, 2);
+        let header = b"P6\n10 6\n255\n";
+
+        assert_eq!(&header[..], &image[..header.len()]);
+        assert_eq!(header.len() + 10 * 6 * 3, image.len());
+        assert_eq!(&[0, 0, 255], &image[header.len()..header.len() + 3]);
+        assert_eq!(&[0, 0, 0], &image[image.len() - 3..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expecting a non-zero width and cell size, got 0 and 2")]
+    fn test_to_ppm_zero_width() {
+        heatmap_program(&PROGRAM, &21).to_ppm(0, 2);
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let heatmap = heatmap_program(&PROGRAM, &21);
+        let view = heatmap.to_ansi(5);
+        let lines: Vec<&str> = view.lines().collect();
+
+        assert_eq!(4, lines.len());
+        assert!(lines[0].starts_with(" 0 "));
+        assert!(lines[1].starts_with(" 5 "));
+        assert!(lines[2].starts_with("10 "));
+    }
+}
@@ -0,0 +1,376 @@
+//! A conservative optimiser for Intcode images.
+//!
+//! Cells that no reachable instruction ever writes are constants. Using that,
+//! the optimiser:
+//!
+//! * folds arithmetic and comparisons whose operands are all constant into a
+//!   single `ADD value, 0` of immediates,
+//! * resolves conditional jumps on constant conditions, dropping the branches
+//!   that can never be taken,
+//! * removes everything that is neither reachable code nor data referenced by
+//!   reachable code, relocating the addresses that remain.
+//!
+//! Every step is only taken when it is provably safe. Images with control flow
+//! that can't be resolved statically are returned unchanged, and instructions
+//! that are themselves written or read as data are never touched, which also
+//! rules out compacting most self-modifying programs.
+
+use super::*;
+use super::disassembler::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, PartialEq)]
+pub struct Optimised {
+    pub memory: Vec<i64>,
+    pub folded: usize,
+    pub removed_cells: usize,
+}
+
+pub fn optimise(memory: &[i64]) -> Optimised {
+    let unchanged = Optimised { memory: memory.to_vec(), folded: 0, removed_cells: 0 };
+    let analysis = match Analysis::new(memory) {
+        Some(analysis) => analysis,
+        None => return unchanged,
+    };
+
+    let mut replaced = BTreeMap::new();
+    let mut removed = 0;
+    let mut instructions = BTreeMap::new();
+    for (address, instruction) in &analysis.disassembly.instructions {
+        match analysis.fold(instruction) {
+            Some(Folded::Removed) => removed += 1,
+            Some(Folded::Replaced(replacement)) => {
+                replaced.insert(*address, replacement.clone());
+                instructions.insert(*address, replacement);
+            }
+            None => { instructions.insert(*address, instruction.clone()); }
+        }
+    }
+
+    match analysis.compact(&instructions) {
+        Some(compacted) => Optimised {
+            removed_cells: memory.len() - compacted.len(),
+            memory: compacted,
+            folded: replaced.len() + removed,
+        },
+        // Jumps that are never taken can only be dropped when compacting, so
+        // in place they are left as they were.
+        None => {
+            let mut image = memory.to_vec();
+            for (address, replacement) in &replaced {
+                image.splice(*address..replacement.next_address(), replacement.encode());
+            }
+            Optimised { memory: image, folded: replaced.len(), removed_cells: 0 }
+        }
+    }
+}
+
+/// Runs both images on each of `inputs`, checking that they output the same.
+pub fn verify(original: &[i64], optimised: &[i64], inputs: &[i64]) -> Result<(), String> {
+    for input in inputs {
+        let expected = run_program(&original.to_vec(), input).output;
+        let actual = run_program(&optimised.to_vec(), input).output;
+        if expected != actual {
+            return Err(format!("Input {}: expected output {:?}, got {:?}", input, expected, actual));
+        }
+    }
+    Ok(())
+}
+
+enum Folded {
+    Replaced(Instruction),
+    Removed,
+}
+
+struct Analysis {
+    disassembly: Disassembly,
+    // Until the code has been disassembled once, nothing is known about which
+    // cells are written.
+    writes_known: bool,
+    written: BTreeSet<usize>,
+    read: BTreeSet<usize>,
+}
+
+impl Analysis {
+    // Disassembles repeatedly, each time pruning jumps using the constants
+    // known from the previous pass. Every pass over-approximates the code that
+    // can run, so reachable code only shrinks until it settles.
+    fn new(memory: &[i64]) -> Option<Analysis> {
+        let mut analysis = Analysis {
+            disassembly: Disassembly { memory: memory.to_vec(), instructions: BTreeMap::new(), unresolved: vec![] },
+            writes_known: false,
+            written: BTreeSet::new(),
+            read: BTreeSet::new(),
+        };
+        loop {
+            let next = Analysis::from(disassemble_with(memory, |i| analysis.successors(i)));
+            if !next.disassembly.is_complete() {
+                return None;
+            }
+            if next.disassembly == analysis.disassembly {
+                break;
+            }
+            analysis = next;
+        }
+
+        let overlapping = analysis.disassembly.instructions.values()
+            .any(|i| (i.address + 1..i.next_address()).any(|a| analysis.disassembly.instructions.contains_key(&a)));
+        if overlapping {
+            return None;
+        }
+        Some(analysis)
+    }
+
+    fn from(disassembly: Disassembly) -> Analysis {
+        let written = disassembly.instructions.values().filter_map(|i| i.write()).collect();
+        let read = disassembly.instructions.values().flat_map(|i| i.reads()).collect();
+        Analysis { disassembly, writes_known: true, written, read }
+    }
+
+    fn is_constant(&self, address: usize) -> bool {
+        self.writes_known && address < self.disassembly.memory.len() && !self.written.contains(&address)
+    }
+
+    fn value(&self, parameter: &Parameter) -> Option<i64> {
+        match parameter.mode {
+            Mode::Immediate => Some(parameter.value),
+            Mode::Position if parameter.value >= 0 && self.is_constant(parameter.value as usize) =>
+                Some(self.disassembly.memory[parameter.value as usize]),
+            Mode::Position => None,
+        }
+    }
+
+    // Instructions that are never written or read as data always execute as
+    // they were decoded, and can be rewritten.
+    fn is_stable(&self, instruction: &Instruction) -> bool {
+        (instruction.address..instruction.next_address()).all(|a| !self.written.contains(&a) && !self.read.contains(&a))
+    }
+
+    // Whether a jump is taken, and where to, when that is known.
+    fn jump(&self, instruction: &Instruction) -> (Option<bool>, Option<usize>) {
+        let condition = self.value(&instruction.parameters[0]).map(|c| match instruction.operation {
+            Operation::JumpIfTrue => c != 0,
+            _ => c == 0,
+        });
+        let target = self.value(&instruction.parameters[1]).filter(|t| *t >= 0).map(|t| t as usize);
+        (condition, target)
+    }
+
+    // Instructions that may be rewritten before they run have unknown
+    // successors, except on the first pass which can only assume the code runs
+    // as it is.
+    fn successors(&self, instruction: &Instruction) -> Successors {
+        let is_unchanged = |a| !self.writes_known || self.is_constant(a);
+        if !is_unchanged(instruction.address) {
+            return Successors::Unknown;
+        }
+        if !instruction.is_jump() {
+            return successors(instruction);
+        }
+        if !(instruction.address..instruction.next_address()).all(is_unchanged) {
+            return Successors::Unknown;
+        }
+        match self.jump(instruction) {
+            (Some(false), _) => Successors::Known(vec![instruction.next_address()]),
+            (Some(true), Some(target)) => Successors::Known(vec![target]),
+            (None, Some(target)) => Successors::Known(vec![instruction.next_address(), target]),
+            (_, None) => Successors::Unknown,
+        }
+    }
+
+    fn fold(&self, instruction: &Instruction) -> Option<Folded> {
+        if !self.is_stable(instruction) {
+            return None;
+        }
+        let immediate = |value| Parameter { mode: Mode::Immediate, value };
+
+        let folded = match instruction.operation {
+            Operation::Add | Operation::Multiply | Operation::LessThan | Operation::Equals => {
+                let a = self.value(&instruction.parameters[0])?;
+                let b = self.value(&instruction.parameters[1])?;
+                let result = match instruction.operation {
+                    Operation::Add => a.checked_add(b)?,
+                    Operation::Multiply => a.checked_mul(b)?,
+                    Operation::LessThan => (a < b) as i64,
+                    _ => (a == b) as i64,
+                };
+                let parameters = vec![immediate(result), immediate(0), instruction.parameters[2]];
+                Folded::Replaced(Instruction { address: instruction.address, operation: Operation::Add, parameters })
+            }
+            Operation::Output => {
+                let value = self.value(&instruction.parameters[0])?;
+                Folded::Replaced(Instruction { parameters: vec![immediate(value)], ..instruction.clone() })
+            }
+            Operation::JumpIfTrue | Operation::JumpIfFalse => match self.jump(instruction) {
+                (Some(false), _) => Folded::Removed,
+                (Some(true), Some(target)) => Folded::Replaced(Instruction {
+                    address: instruction.address,
+                    operation: Operation::JumpIfTrue,
+                    parameters: vec![immediate(1), immediate(target as i64)],
+                }),
+                (None, Some(target)) if instruction.parameters[1].mode == Mode::Position => Folded::Replaced(Instruction {
+                    parameters: vec![instruction.parameters[0], immediate(target as i64)],
+                    ..instruction.clone()
+                }),
+                _ => return None,
+            },
+            Operation::Input | Operation::Halt => return None,
+        };
+
+        match folded {
+            Folded::Replaced(ref replacement) if replacement == instruction => None,
+            folded => Some(folded),
+        }
+    }
+
+    // Lays out the kept instructions and referenced data contiguously, in their
+    // original order, and relocates every address. Gives up if any address
+    // could change at runtime or if code doubles as data.
+    fn compact(&self, instructions: &BTreeMap<usize, Instruction>) -> Option<Vec<i64>> {
+        let memory = &self.disassembly.memory;
+        let code_as_data = self.written.iter().chain(self.read.iter()).any(|a| self.disassembly.is_code(*a));
+        let dynamic_addresses = self.disassembly.instructions.values().any(|i| {
+            i.parameters.iter().enumerate().any(|(p, parameter)| {
+                let is_address = parameter.mode == Mode::Position || (i.is_jump() && p == 1);
+                is_address && (!self.is_constant(i.address + 1 + p) || parameter.value < 0 || parameter.value as usize >= memory.len())
+            })
+        });
+        let unresolved_jumps = instructions.values().any(|i| i.is_jump() && i.parameters[1].mode == Mode::Position);
+        if code_as_data || dynamic_addresses || unresolved_jumps {
+            return None;
+        }
+
+        // Unconditional jumps to what ends up being the next instruction are
+        // dropped too.
+        let mut instructions = instructions.clone();
+        let jumps: Vec<usize> = instructions.keys().cloned().collect();
+        for address in jumps {
+            let instruction = instructions[&address].clone();
+            let unconditional = instruction.is_jump() && instruction.parameters[0] == (Parameter { mode: Mode::Immediate, value: 1 });
+            let next = instructions.range(address + 1..).next().map(|(a, _)| *a);
+            if unconditional && next.is_some() && next == instructions.range(instruction.parameters[1].value as usize..).next().map(|(a, _)| *a) {
+                instructions.remove(&address);
+            }
+        }
+
+        let data: BTreeSet<usize> = instructions.values().flat_map(|i| i.reads().into_iter().chain(i.write())).collect();
+        let mut relocated = BTreeMap::new();
+        let mut kept = vec![];
+        let mut address = 0;
+        while address < memory.len() {
+            if let Some(instruction) = instructions.get(&address) {
+                relocated.insert(address, kept.len());
+                kept.extend(instruction.encode());
+                address = instruction.next_address();
+            } else {
+                if data.contains(&address) {
+                    relocated.insert(address, kept.len());
+                    kept.push(memory[address]);
+                }
+                address += 1;
+            }
+        }
+
+        let mut image = kept;
+        for instruction in instructions.values() {
+            let start = relocated[&instruction.address];
+            for (p, parameter) in instruction.parameters.iter().enumerate() {
+                let cell = &mut image[start + 1 + p];
+                if instruction.is_jump() && p == 1 {
+                    // Removed jumps fall through to the next kept instruction.
+                    let target = instructions.range(parameter.value as usize..).next()?.0;
+                    *cell = relocated[target] as i64;
+                } else if parameter.mode == Mode::Position {
+                    *cell = relocated[&(parameter.value as usize)] as i64;
+                }
+            }
+        }
+        Some(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs 999, 1000 or 1001 depending on whether the input is below, equal
+    // to or above 8.
+    const COMPARE_TO_EIGHT: [i64; 47] = [3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+
+    #[test]
+    fn test_fold_constant_arithmetic() {
+        let memory = vec![1102, 3, 4, 9, 4, 9, 99, 0, 0, 0];
+        let optimised = optimise(&memory);
+
+        assert_eq!(vec![1101, 12, 0, 7, 4, 7, 99, 0], optimised.memory);
+        assert_eq!(1, optimised.folded);
+        assert_eq!(2, optimised.removed_cells);
+    }
+
+    #[test]
+    fn test_fold_constant_data() {
+        // Outputs 2 * 21 where 21 is never written.
+        let memory = vec![1002, 8, 2, 9, 4, 9, 99, 0, 21, 0];
+        let optimised = optimise(&memory);
+
+        assert_eq!(vec![1101, 42, 0, 7, 4, 7, 99, 0], optimised.memory);
+    }
+
+    #[test]
+    fn test_remove_unreachable_code() {
+        let memory = vec![1105, 1, 5, 77, 88, 104, 42, 99];
+
+        assert_eq!(vec![104, 42, 99], optimise(&memory).memory);
+    }
+
+    #[test]
+    fn test_remove_never_taken_branch() {
+        let memory = vec![1106, 1, 6, 104, 1, 99, 104, 2, 99];
+
+        assert_eq!(vec![104, 1, 99], optimise(&memory).memory);
+    }
+
+    #[test]
+    fn test_keep_branches_on_input() {
+        let optimised = optimise(&COMPARE_TO_EIGHT);
+
+        assert!(optimised.memory.len() < COMPARE_TO_EIGHT.len());
+        assert_eq!(Ok(()), verify(&COMPARE_TO_EIGHT, &optimised.memory, &[-5, 7, 8, 9, 100]));
+    }
+
+    #[test]
+    fn test_self_modifying_code_is_left_alone() {
+        // Patches the instruction at 4 into an output of 2.
+        let memory = vec![1001, 4, 3, 4, 101, 2, 99];
+
+        assert_eq!(memory, optimise(&memory).memory);
+    }
+
+    #[test]
+    fn test_unresolved_control_flow_is_left_alone() {
+        // Jumps to the address read from input.
+        let memory = vec![3, 9, 1005, 9, 9, 104, 1, 99, 0, 0];
+        let optimised = optimise(&memory);
+
+        assert_eq!(memory, optimised.memory);
+        assert_eq!(0, optimised.folded);
+    }
+
+    #[test]
+    fn test_in_place_folding_when_code_is_read_as_data() {
+        // Outputs its own first cell, then a foldable multiplication.
+        let memory = vec![1102, 2, 3, 13, 4, 0, 1102, 1, 1, 13, 4, 13, 99, 0];
+        let optimised = optimise(&memory);
+
+        assert_eq!(vec![1102, 2, 3, 13, 104, 1102, 1101, 1, 0, 13, 4, 13, 99, 0], optimised.memory);
+        assert_eq!(2, optimised.folded);
+        assert_eq!(Ok(()), verify(&memory, &optimised.memory, &[0]));
+    }
+
+    #[test]
+    fn test_verify() {
+        assert_eq!(Ok(()), verify(&[104, 1, 99], &[104, 1, 99], &[0]));
+        assert!(verify(&[104, 1, 99], &[104, 2, 99], &[0]).is_err());
+    }
+}
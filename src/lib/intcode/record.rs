@@ -0,0 +1,170 @@
+//! Record mode for the Intcode VM.
+//!
+//! A recorded run keeps every executed `Step`, so the program state can be
+//! rewound one instruction at a time, or straight to the last instruction that
+//! wrote a given address.
+
+use super::*;
+
+pub fn record_program(memory: &[i64], input: &i64) -> Recording {
+    let mut steps = vec![];
+    let result = run_program_observed(memory, input, Decoding::Lenient, |step| steps.push(step.clone()))
+        .expect("Lenient decoding never fails");
+
+    Recording {
+        position: steps.len(),
+        steps,
+        memory: result.memory,
+    }
+}
+
+/// A recorded run, positioned at some point of its execution. It starts at the
+/// end of the run.
+#[derive(Debug)]
+pub struct Recording {
+    steps: Vec<Step>,
+    position: usize,
+    memory: Vec<i64>,
+}
+
+impl Recording {
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// How many steps have been executed at the current point.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn instruction_pointer(&self) -> usize {
+        match self.position {
+            0 => 0,
+            position => self.steps[position - 1].next_instruction_pointer,
+        }
+    }
+
+    pub fn output(&self) -> Vec<i64> {
+        self.steps[..self.position].iter().filter_map(|step| step.output).collect()
+    }
+
+    /// Undoes the last executed step, returning it.
+    pub fn step_back(&mut self) -> Option<Step> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let step = self.steps[self.position].clone();
+        if let Some(write) = step.write {
+            self.memory[write.address] = write.old;
+        }
+        Some(step)
+    }
+
+    /// Re-executes the next recorded step, returning it.
+    pub fn step_forward(&mut self) -> Option<Step> {
+        let step = self.steps.get(self.position)?.clone();
+        if let Some(write) = step.write {
+            self.memory[write.address] = write.new;
+        }
+        self.position += 1;
+        Some(step)
+    }
+
+    /// Moves to the given position, stepping backwards or forwards as needed.
+    pub fn seek(&mut self, position: usize) {
+        while self.position > position && self.step_back().is_some() {}
+        while self.position < position && self.step_forward().is_some() {}
+    }
+
+    /// The last step before the current point that wrote to `address`, along
+    /// with its index.
+    pub fn last_write(&self, address: usize) -> Option<(usize, Step)> {
+        self.steps[..self.position]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, step)| step.write.map(|w| w.address) == Some(address))
+            .map(|(index, step)| (index, step.clone()))
+    }
+
+    /// Rewinds to just before the last step that wrote to `address`, so that
+    /// the memory shows the operands it was computed from. Returns that step,
+    /// or `None` (without moving) if nothing wrote to the address.
+    pub fn rewind_to_last_write(&mut self, address: usize) -> Option<Step> {
+        let (index, step) = self.last_write(address)?;
+        self.seek(index);
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reads the input into 11, doubles it into 12 and outputs it.
+    const PROGRAM: [i64; 13] = [3, 11, 1002, 11, 2, 12, 4, 12, 99, 0, 0, 0, 0];
+
+    #[test]
+    fn test_record_program() {
+        let recording = record_program(&PROGRAM, &21);
+
+        assert_eq!(3, recording.steps().len());
+        assert_eq!(3, recording.position());
+        assert_eq!(8, recording.instruction_pointer());
+        assert_eq!(run_program(&PROGRAM.to_vec(), &21).memory, recording.memory());
+        assert_eq!(vec![42], recording.output());
+    }
+
+    #[test]
+    fn test_step_back_and_forward() {
+        let mut recording = record_program(&PROGRAM, &21);
+
+        assert_eq!(Some(6), recording.step_back().map(|s| s.instruction_pointer));
+        assert_eq!(Vec::<i64>::new(), recording.output());
+        assert_eq!(Some(2), recording.step_back().map(|s| s.instruction_pointer));
+        assert_eq!(0, recording.memory()[12]);
+        assert_eq!(2, recording.instruction_pointer());
+        assert_eq!(Some(0), recording.step_back().map(|s| s.instruction_pointer));
+        assert_eq!(PROGRAM, recording.memory());
+        assert_eq!(None, recording.step_back());
+
+        assert_eq!(Some(0), recording.step_forward().map(|s| s.instruction_pointer));
+        assert_eq!(21, recording.memory()[11]);
+        recording.seek(3);
+        assert_eq!(42, recording.memory()[12]);
+        assert_eq!(None, recording.step_forward());
+    }
+
+    #[test]
+    fn test_last_write() {
+        let mut recording = record_program(&PROGRAM, &21);
+
+        let (index, step) = recording.last_write(12).unwrap();
+        assert_eq!(1, index);
+        assert_eq!(2, step.instruction_pointer);
+        assert_eq!(Some(MemoryWrite{address: 12, old: 0, new: 42}), step.write);
+        assert_eq!(None, recording.last_write(10));
+
+        recording.seek(1);
+        assert_eq!(None, recording.last_write(12));
+    }
+
+    #[test]
+    fn test_rewind_to_last_write() {
+        let mut recording = record_program(&PROGRAM, &21);
+
+        assert_eq!(Some(2), recording.rewind_to_last_write(12).map(|s| s.instruction_pointer));
+        assert_eq!(1, recording.position());
+        assert_eq!(2, recording.instruction_pointer());
+        assert_eq!(21, recording.memory()[11]);
+        assert_eq!(0, recording.memory()[12]);
+
+        assert_eq!(None, recording.rewind_to_last_write(10));
+        assert_eq!(1, recording.position());
+    }
+}
@@ -1,10 +1,22 @@
 use std::fs; 
 
+extern crate ansi_term;
+
+pub mod geometry;
+pub mod grid;
+pub mod inputs;
 pub mod intcode;
+pub mod parse;
+pub mod passwords;
+pub mod solution;
+pub mod wires;
+
+pub use parse::{ParseError, parse_lines, parse_list};
+pub use solution::{Answer, Answers, Measure, Part, Solution, Solved, Timings, measure_parts, solve, solve_timed};
 
-pub fn read_inputs(filename: &str) -> String {
+pub fn read_inputs(filename: &str) -> Result<String, String> {
     return fs::read_to_string(filename)
-    .expect("Couldn't read file");
+    .map_err(|error| format!("Couldn't read {}: {}", filename, error));
 }
 
 pub fn split_lines_into_vec_str(input: &str) -> Vec<&str> {
@@ -16,20 +28,17 @@ pub fn split_lines_into_vec_int(input: &str) -> Vec<i64> {
     return split_into_vec_int(input, "\n");
 }
 
+/// Panics on anything that isn't an integer; `parse_list` returns the error.
 pub fn split_into_vec_int(input: &str, delimiter: &str) -> Vec<i64> {
-    return input.split(delimiter)
-    .into_iter()
-    .filter(|&line| line.ne(""))
-    .map(|line| line.parse::<i64>().expect("Couldn't convert to i64"))
-    .collect()
+    return parse_list(input, delimiter)
+    .unwrap_or_else(|error| panic!("{}", error));
 }
 
+/// Panics on anything that isn't an unsigned integer; `parse_list` returns the
+/// error.
 pub fn split_into_vec_usize(input: &str, delimiter: &str) -> Vec<usize> {
-    return input.split(delimiter)
-    .into_iter()
-    .filter(|&line| line.ne(""))
-    .map(|line| line.parse::<usize>().expect("Couldn't convert to i64"))
-    .collect()
+    return parse_list(input, delimiter)
+    .unwrap_or_else(|error| panic!("{}", error));
 }
 
 pub fn run_function_and_sum_all(f: fn(&i64) -> i64, elements: &Vec<i64>) -> i64 {
@@ -41,6 +50,13 @@ pub fn run_function_and_sum_all(f: fn(&i64) -> i64, elements: &Vec<i64>) -> i64
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_inputs_missing_file() {
+        let error = read_inputs("inputs/missing.txt").unwrap_err();
+
+        assert!(error.starts_with("Couldn't read inputs/missing.txt: "));
+    }
+
     #[test]
     fn test_split_lines_into_vec_int() {
         let input = "1\n2\n999\n";
@@ -63,4 +79,10 @@ mod tests {
 
         assert_eq!(vec![1,2,999], split_into_vec_usize(input, &delimiter));
     }
+
+    #[test]
+    #[should_panic(expected = "Expected usize at line 1, column 5, got '-2'")]
+    fn test_split_into_vec_usize_negative() {
+        split_into_vec_usize("1 | -2", " | ");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,132 @@
+//! Fallible parsing of puzzle inputs. Errors say what was expected and point
+//! at where in the input the offending token is, so days can pass them up
+//! with `?` instead of panicking.
+
+use std::any;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// What couldn't be parsed, empty if the input ended early.
+    pub token: String,
+    /// What the token should have been, like `i64` or `a second line`.
+    pub expected: String,
+    /// 1-based line of the token.
+    pub line: usize,
+    /// 1-based column of the token, in characters.
+    pub column: usize,
+}
+
+impl ParseError {
+    /// An error for `token`, starting `offset` bytes into `input`.
+    pub fn at(input: &str, offset: usize, token: &str, expected: &str) -> ParseError {
+        let (line, column) = position(input, offset);
+        ParseError {
+            token: token.to_string(),
+            expected: expected.to_string(),
+            line,
+            column,
+        }
+    }
+
+    /// An error for input that ended before `expected` was found.
+    pub fn end_of_input(input: &str, expected: &str) -> ParseError {
+        ParseError::at(input, input.len(), "", expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.token.is_empty() {
+            write!(f, "Expected {} at line {}, column {}, but the input ended", self.expected, self.line, self.column)
+        } else {
+            write!(f, "Expected {} at line {}, column {}, got '{}'", self.expected, self.line, self.column, self.token)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The 1-based line and column of the byte at `offset` in `input`.
+pub fn position(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Parses every `delimiter` separated token of `input` as a `T`. Tokens are
+/// trimmed, and empty ones, like after a trailing newline, are skipped.
+pub fn parse_list<T: FromStr>(input: &str, delimiter: &str) -> Result<Vec<T>, ParseError> {
+    tokens(input, delimiter)
+        .map(|(offset, token)| token.parse::<T>()
+            .map_err(|_| ParseError::at(input, offset, token, any::type_name::<T>())))
+        .collect()
+}
+
+/// Parses each non-empty line of `input` as a `T`.
+pub fn parse_lines<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    parse_list(input, "\n")
+}
+
+// Non-empty trimmed tokens, along with their offset in the input.
+fn tokens<'a>(input: &'a str, delimiter: &'a str) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    let mut offset = 0;
+    input.split(delimiter).filter_map(move |token| {
+        let start = offset;
+        offset += token.len() + delimiter.len();
+
+        let trimmed = token.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let leading = token.len() - token.trim_start().len();
+        Some((start + leading, trimmed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(Ok(vec![1, -2, 999]), parse_list::<i64>("1,-2,999\n", ","));
+        assert_eq!(Ok(vec![1, 2, 999]), parse_list::<usize>("1 | 2 | 999", " | "));
+        assert_eq!(Ok(vec![4, 5]), parse_lines::<u32>("4\r\n5\r\n\r\n"));
+        assert_eq!(Ok(Vec::<i32>::new()), parse_list::<i32>("", ","));
+    }
+
+    #[test]
+    fn test_parse_list_errors() {
+        assert_eq!(
+            Err(ParseError { token: "x".to_string(), expected: "i64".to_string(), line: 1, column: 5 }),
+            parse_list::<i64>("1,2,x,4", ",")
+        );
+        assert_eq!(
+            Err(ParseError { token: "-3".to_string(), expected: "usize".to_string(), line: 3, column: 2 }),
+            parse_lines::<usize>("1\n2\n -3\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        assert_eq!(
+            "Expected i64 at line 2, column 3, got 'abc'",
+            ParseError::at("1\n2,abc", 4, "abc", "i64").to_string()
+        );
+        assert_eq!(
+            "Expected a second line at line 1, column 4, but the input ended",
+            ParseError::end_of_input("R75", "a second line").to_string()
+        );
+    }
+
+    #[test]
+    fn test_position() {
+        assert_eq!((1, 1), position("abc", 0));
+        assert_eq!((2, 1), position("abc\ndef", 4));
+        assert_eq!((2, 3), position("abc\n█ef", 8));
+    }
+}
@@ -0,0 +1,383 @@
+//! Day 4's passwords: numbers written with a fixed number of digits in some
+//! base, padded with leading zeros, that must follow a set of rules.
+//!
+//! Rules look at the digits from the most significant one, and combine with
+//! `All`, `Any` and `Not`, so day 4's two parts are
+//!
+//! ```text
+//! Passwords::new(6, 10).with_rule(Rule::NonDecreasing).with_rule(Rule::RunAtLeast(2))
+//! Passwords::new(6, 10).with_rule(Rule::NonDecreasing).with_rule(Rule::RunExactly(2))
+//! ```
+//!
+//! `valid_between` checks every value in a range, which is fine for six
+//! digits. `count_between` counts them digit by digit instead, keeping only
+//! what the rules need to know about the digits so far, so it handles
+//! lengths of 18 digits and more.
+
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// No digit is smaller than the one before it.
+    NonDecreasing,
+    /// Some digit repeats at least this many times in a row.
+    RunAtLeast(usize),
+    /// Some digit repeats exactly this many times in a row, not as part of a
+    /// longer run.
+    RunExactly(usize),
+    /// None of these digits appear.
+    ForbiddenDigits(Vec<u32>),
+    /// The digits add up to between `min` and `max`, both included.
+    DigitSum { min: u32, max: u32 },
+    All(Vec<Rule>),
+    Any(Vec<Rule>),
+    Not(Box<Rule>),
+}
+
+impl Rule {
+    pub fn check(&self, digits: &[u32]) -> bool {
+        match self {
+            Rule::NonDecreasing => digits.windows(2).all(|pair| pair[0] <= pair[1]),
+            Rule::RunAtLeast(length) => runs(digits).any(|run| run >= *length),
+            Rule::RunExactly(length) => runs(digits).any(|run| run == *length),
+            Rule::ForbiddenDigits(forbidden) => !digits.iter().any(|digit| forbidden.contains(digit)),
+            Rule::DigitSum { min, max } => {
+                let sum: u32 = digits.iter().sum();
+                *min <= sum && sum <= *max
+            }
+            Rule::All(rules) => rules.iter().all(|rule| rule.check(digits)),
+            Rule::Any(rules) => rules.iter().any(|rule| rule.check(digits)),
+            Rule::Not(rule) => !rule.check(digits),
+        }
+    }
+
+    // The same as `check`, from a summary of all the digits.
+    fn holds(&self, summary: &Summary) -> bool {
+        match self {
+            Rule::NonDecreasing => !summary.decreased,
+            Rule::RunAtLeast(length) => summary.runs_at_least.contains(length),
+            Rule::RunExactly(length) => summary.runs_exactly.contains(length),
+            Rule::ForbiddenDigits(forbidden) => forbidden.iter().all(|&digit| digit >= 36 || summary.digits & 1 << digit == 0),
+            Rule::DigitSum { min, max } => *min <= summary.sum && summary.sum <= *max,
+            Rule::All(rules) => rules.iter().all(|rule| rule.holds(summary)),
+            Rule::Any(rules) => rules.iter().any(|rule| rule.holds(summary)),
+            Rule::Not(rule) => !rule.holds(summary),
+        }
+    }
+
+    // Folds into `tracking` what the rule needs to know about the digits.
+    fn track(&self, tracking: &mut Tracking) {
+        match self {
+            Rule::NonDecreasing => {}
+            Rule::RunAtLeast(length) => {
+                tracking.run_cap = tracking.run_cap.max(*length);
+                tracking.at_least.insert(*length);
+            }
+            Rule::RunExactly(length) => {
+                tracking.run_cap = tracking.run_cap.max(length + 1);
+                tracking.exactly.insert(*length);
+            }
+            Rule::ForbiddenDigits(forbidden) => tracking.digits |= forbidden.iter().filter(|&&digit| digit < 36).fold(0, |mask, digit| mask | 1 << digit),
+            Rule::DigitSum { max, .. } => tracking.sum_cap = tracking.sum_cap.max(max.saturating_add(1)),
+            Rule::All(rules) | Rule::Any(rules) => rules.iter().for_each(|rule| rule.track(tracking)),
+            Rule::Not(rule) => rule.track(tracking),
+        }
+    }
+}
+
+// How much of the digits to remember, so that passwords that look the same to
+// the rules share a summary: only the run lengths and digits the rules ask
+// about, and runs and sums up to the largest ones they ask about.
+#[derive(Debug, Default)]
+struct Tracking {
+    run_cap: usize,
+    at_least: BTreeSet<usize>,
+    exactly: BTreeSet<usize>,
+    digits: u64,
+    sum_cap: u32,
+}
+
+// Everything the rules can ask about a password's digits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct Summary {
+    last: Option<u32>,
+    // The length of the run `last` is part of.
+    run: usize,
+    // The lengths the rules ask about that some finished run was at least, or
+    // exactly.
+    runs_at_least: BTreeSet<usize>,
+    runs_exactly: BTreeSet<usize>,
+    decreased: bool,
+    // Bit d is set when digit d appears.
+    digits: u64,
+    sum: u32,
+}
+
+impl Summary {
+    fn push(&self, digit: u32, tracking: &Tracking) -> Summary {
+        let mut next = if self.last == Some(digit) {
+            Summary { run: (self.run + 1).min(tracking.run_cap), ..self.clone() }
+        } else {
+            Summary { last: Some(digit), run: 1.min(tracking.run_cap), ..self.finished(tracking) }
+        };
+        next.decreased |= self.last.is_some_and(|last| digit < last);
+        next.digits |= 1 << digit & tracking.digits;
+        next.sum = self.sum.saturating_add(digit).min(tracking.sum_cap);
+        next
+    }
+
+    // Counts the run in progress as finished.
+    fn finished(&self, tracking: &Tracking) -> Summary {
+        let mut finished = self.clone();
+        if self.last.is_some() {
+            finished.runs_at_least.extend(tracking.at_least.range(..=self.run));
+            if tracking.exactly.contains(&self.run) {
+                finished.runs_exactly.insert(self.run);
+            }
+        }
+        finished
+    }
+}
+
+// The lengths of the runs of equal digits, in order.
+fn runs(digits: &[u32]) -> impl Iterator<Item = usize> + '_ {
+    digits.chunk_by(|a, b| a == b).map(|run| run.len())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passwords {
+    pub length: usize,
+    pub base: u32,
+    pub rules: Vec<Rule>,
+}
+
+impl Passwords {
+    /// Passwords of `length` digits in `base`, from 2 to 36, with no rules
+    /// yet.
+    pub fn new(length: usize, base: u32) -> Passwords {
+        assert!((2..=36).contains(&base), "Expecting a base between 2 and 36, got {}", base);
+        Passwords { length, base, rules: vec![] }
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Passwords {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The number of values with `length` digits, if it fits.
+    pub fn size(&self) -> Option<u128> {
+        (self.base as u128).checked_pow(self.length as u32)
+    }
+
+    /// The digits of `value`, most significant first and padded to `length`,
+    /// or `None` if it needs more digits than that.
+    pub fn digits(&self, value: u128) -> Option<Vec<u32>> {
+        let mut digits = vec![0; self.length];
+        let mut rest = value;
+        for digit in digits.iter_mut().rev() {
+            *digit = (rest % self.base as u128) as u32;
+            rest /= self.base as u128;
+        }
+        if rest == 0 { Some(digits) } else { None }
+    }
+
+    pub fn is_valid(&self, value: u128) -> bool {
+        match self.digits(value) {
+            Some(digits) => self.rules.iter().all(|rule| rule.check(&digits)),
+            None => false,
+        }
+    }
+
+    /// Every valid password from `min` to `max`, both included, found by
+    /// checking each of them in turn.
+    pub fn valid_between(&self, min: u128, max: u128) -> Vec<u128> {
+        let max = match self.size() {
+            Some(size) => max.min(size - 1),
+            None => max,
+        };
+        (min..=max).filter(|&value| self.is_valid(value)).collect()
+    }
+
+    /// The number of valid passwords from `min` to `max`, both included,
+    /// without going through them one by one. That's `None` only when it's
+    /// every one of the 2^128 values a `u128` can hold, one too many for it.
+    pub fn count_between(&self, min: u128, max: u128) -> Option<u128> {
+        let max = match self.size() {
+            Some(size) => max.min(size - 1),
+            None => max,
+        };
+        match (min, max) {
+            (min, max) if min > max => Some(0),
+            (0, max) => self.count_up_to(max),
+            (min, max) => match self.count_up_to(max) {
+                Some(count) => Some(count - self.count_up_to(min - 1)?),
+                // Everything up to `max` is valid, so everything from `min` is.
+                None => Some(max - min + 1),
+            },
+        }
+    }
+
+    // Counts valid passwords up to `bound` by going through its digits, keeping
+    // how many prefixes already below it share each summary, and the one
+    // prefix that has followed it so far. The prefixes below never add up to
+    // more than `bound`, so only counting `bound` itself can overflow.
+    fn count_up_to(&self, bound: u128) -> Option<u128> {
+        let mut tracking = Tracking::default();
+        self.rules.iter().for_each(|rule| rule.track(&mut tracking));
+        let valid = |summary: &Summary| self.rules.iter().all(|rule| rule.holds(&summary.finished(&tracking)));
+
+        let mut below: HashMap<Summary, u128> = HashMap::new();
+        let mut along = Summary::default();
+        for bound_digit in self.digits(bound).expect("Expecting a bound within the password length") {
+            let mut next = HashMap::new();
+            for (summary, count) in &below {
+                for digit in 0..self.base {
+                    *next.entry(summary.push(digit, &tracking)).or_insert(0) += count;
+                }
+            }
+            for digit in 0..bound_digit {
+                *next.entry(along.push(digit, &tracking)).or_insert(0) += 1;
+            }
+            along = along.push(bound_digit, &tracking);
+            below = next;
+        }
+
+        let count: u128 = below.iter().filter(|(summary, _)| valid(summary)).map(|(_, count)| count).sum();
+        if valid(&along) { count.checked_add(1) } else { Some(count) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(value: u128) -> Vec<u32> {
+        Passwords::new(6, 10).digits(value).unwrap()
+    }
+
+    #[test]
+    fn test_digits() {
+        let passwords = Passwords::new(6, 10);
+
+        assert_eq!(Some(vec![1, 7, 2, 9, 3, 0]), passwords.digits(172930));
+        assert_eq!(Some(vec![0, 0, 0, 0, 4, 2]), passwords.digits(42));
+        assert_eq!(None, passwords.digits(1000000));
+        assert_eq!(Some(vec![1, 0, 1, 0]), Passwords::new(4, 2).digits(10));
+        assert_eq!(Some(vec![15, 15]), Passwords::new(2, 16).digits(255));
+    }
+
+    #[test]
+    fn test_run_rules() {
+        assert!(Rule::RunAtLeast(2).check(&decimal(111111)));
+        assert!(!Rule::RunAtLeast(2).check(&decimal(123789)));
+        assert!(Rule::RunExactly(2).check(&decimal(112233)));
+        assert!(!Rule::RunExactly(2).check(&decimal(123444)));
+        assert!(Rule::RunExactly(2).check(&decimal(111122)));
+        assert!(Rule::RunExactly(3).check(&decimal(123444)));
+        assert!(!Rule::RunAtLeast(4).check(&decimal(123444)));
+    }
+
+    #[test]
+    fn test_other_rules() {
+        assert!(Rule::NonDecreasing.check(&decimal(111123)));
+        assert!(!Rule::NonDecreasing.check(&decimal(223450)));
+        assert!(Rule::ForbiddenDigits(vec![0, 9]).check(&decimal(123456)));
+        assert!(!Rule::ForbiddenDigits(vec![0, 9]).check(&decimal(123459)));
+        assert!(Rule::DigitSum { min: 21, max: 21 }.check(&decimal(123456)));
+        assert!(!Rule::DigitSum { min: 0, max: 20 }.check(&decimal(123456)));
+    }
+
+    #[test]
+    fn test_combined_rules() {
+        let either = Rule::Any(vec![Rule::RunExactly(2), Rule::DigitSum { min: 0, max: 3 }]);
+        let neither = Rule::Not(Box::new(either.clone()));
+
+        assert!(either.check(&decimal(100002)));
+        assert!(either.check(&decimal(123445)));
+        assert!(!either.check(&decimal(123456)));
+        assert!(neither.check(&decimal(123456)));
+        assert!(Rule::All(vec![]).check(&decimal(123456)));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let part_one = Passwords::new(6, 10).with_rule(Rule::NonDecreasing).with_rule(Rule::RunAtLeast(2));
+        let part_two = Passwords::new(6, 10).with_rule(Rule::NonDecreasing).with_rule(Rule::RunExactly(2));
+
+        assert!(part_one.is_valid(111111));
+        assert!(!part_one.is_valid(223450));
+        assert!(!part_one.is_valid(123789));
+        assert!(!part_one.is_valid(1112222));
+        assert!(part_two.is_valid(112233));
+        assert!(!part_two.is_valid(123444));
+        assert!(part_two.is_valid(111122));
+    }
+
+    #[test]
+    fn test_valid_between() {
+        let passwords = Passwords::new(3, 2).with_rule(Rule::NonDecreasing);
+
+        assert_eq!(vec![0b000, 0b001, 0b011, 0b111], passwords.valid_between(0, 100));
+        assert_eq!(15, Passwords::new(2, 16).with_rule(Rule::RunAtLeast(2)).valid_between(0x10, 0xff).len());
+    }
+
+    #[test]
+    fn test_count_between_agrees_with_valid_between() {
+        let rules = vec![
+            Rule::NonDecreasing,
+            Rule::RunAtLeast(2),
+            Rule::RunExactly(2),
+            Rule::RunExactly(1),
+            Rule::ForbiddenDigits(vec![0, 3]),
+            Rule::DigitSum { min: 5, max: 9 },
+            Rule::Any(vec![Rule::RunExactly(3), Rule::Not(Box::new(Rule::NonDecreasing))]),
+            Rule::All(vec![Rule::NonDecreasing, Rule::RunExactly(2), Rule::DigitSum { min: 0, max: 12 }]),
+        ];
+        for (length, base) in [(5, 4), (4, 10), (7, 3)] {
+            for rule in &rules {
+                let passwords = Passwords::new(length, base).with_rule(rule.clone());
+                let size = passwords.size().unwrap();
+                for (min, max) in [(0, size - 1), (17, size / 2), (size / 3, size + 10), (5, 4)] {
+                    assert_eq!(Some(passwords.valid_between(min, max).len() as u128), passwords.count_between(min, max),
+                        "{:?} for {} digits in base {} between {} and {}", rule, length, base, min, max);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_between_long_passwords() {
+        let non_decreasing = Passwords::new(18, 10).with_rule(Rule::NonDecreasing);
+        let with_double = non_decreasing.clone().with_rule(Rule::RunAtLeast(2));
+
+        // Choosing 18 digits out of 10 with repetition, which always repeats
+        // one of them.
+        assert_eq!(Some(4686825), non_decreasing.count_between(0, u128::MAX));
+        assert_eq!(Some(4686825), with_double.count_between(0, u128::MAX));
+        assert_eq!(Some(10u128.pow(30)), Passwords::new(30, 10).count_between(0, u128::MAX));
+        assert_eq!(Some(2), Passwords::new(40, 2).with_rule(Rule::RunExactly(40)).count_between(0, u128::MAX));
+    }
+
+    #[test]
+    fn test_count_between_every_u128() {
+        let passwords = Passwords::new(40, 10);
+
+        assert_eq!(None, passwords.count_between(0, u128::MAX));
+        assert_eq!(Some(u128::MAX), passwords.count_between(1, u128::MAX));
+        assert_eq!(Some(u128::MAX - 9), passwords.count_between(10, u128::MAX));
+        assert_eq!(Some(u128::MAX), passwords.count_between(0, u128::MAX - 1));
+        assert_eq!(Some(u128::MAX), Passwords::new(128, 2).count_between(0, u128::MAX - 1));
+        assert_eq!(Some(1), Passwords::new(40, 10).with_rule(Rule::RunAtLeast(40)).count_between(0, u128::MAX));
+    }
+
+    #[test]
+    fn test_count_between_long_runs() {
+        let at_least = Passwords::new(200, 2).with_rule(Rule::RunAtLeast(150));
+        let exactly = Passwords::new(200, 2).with_rule(Rule::RunExactly(198));
+
+        assert!(at_least.is_valid(0));
+        assert_eq!(Some(1001), at_least.count_between(0, 1000));
+        assert_eq!(Some(exactly.valid_between(0, 1000).len() as u128), exactly.count_between(0, 1000));
+        assert_eq!(Some(0), Passwords::new(5, 10).with_rule(Rule::RunAtLeast(150)).count_between(0, 99999));
+    }
+}
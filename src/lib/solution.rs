@@ -0,0 +1,213 @@
+use std::fmt;
+use parse::ParseError;
+use std::time::{Duration, Instant};
+
+/// The answer to one part of a puzzle. Anything displayable converts into one,
+/// so parts can return whatever they compute with `.into()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer(String);
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Answer {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(answer: $t) -> Answer {
+                Answer(answer.to_string())
+            }
+        })*
+    };
+}
+
+answer_from!(i32, i64, u32, u64, u128, usize, String, &str);
+
+/// A day's puzzle: parsing its input once, then solving both parts from it.
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part_one(input: &Self::Input) -> Answer;
+
+    fn part_two(input: &Self::Input) -> Answer;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn all() -> [Part; 2] {
+        [Part::One, Part::Two]
+    }
+
+    pub fn number(&self) -> u32 {
+        match *self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Part::One => write!(f, "Part one"),
+            Part::Two => write!(f, "Part two"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Answers {
+    pub part_one: Answer,
+    pub part_two: Answer,
+}
+
+impl Answers {
+    pub fn get(&self, part: Part) -> &Answer {
+        match part {
+            Part::One => &self.part_one,
+            Part::Two => &self.part_two,
+        }
+    }
+}
+
+pub fn solve<S: Solution>(input: &str) -> Result<Answers, ParseError> {
+    solve_timed::<S>(input).map(|solved| solved.answers)
+}
+
+/// How long parsing and each part took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part_one: Duration,
+    pub part_two: Duration,
+}
+
+impl Timings {
+    pub fn get(&self, part: Part) -> Duration {
+        match part {
+            Part::One => self.part_one,
+            Part::Two => self.part_two,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse + self.part_one + self.part_two
+    }
+}
+
+#[derive(Debug)]
+pub struct Solved {
+    pub answers: Answers,
+    pub timings: Timings,
+}
+
+pub fn solve_timed<S: Solution>(input: &str) -> Result<Solved, ParseError> {
+    let (input, parse) = timed(|| S::parse(input));
+    let input = input?;
+    let (part_one, part_one_time) = timed(|| S::part_one(&input));
+    let (part_two, part_two_time) = timed(|| S::part_two(&input));
+
+    Ok(Solved {
+        answers: Answers { part_one, part_two },
+        timings: Timings { parse, part_one: part_one_time, part_two: part_two_time },
+    })
+}
+
+/// Receives each part of a solution as something it can call as many times
+/// as it likes.
+pub type Measure<'a> = &'a mut dyn FnMut(Part, &dyn Fn() -> Answer);
+
+/// Parses the input once, then hands each part to `measure`.
+pub fn measure_parts<S: Solution>(input: &str, measure: Measure) -> Result<(), ParseError> {
+    let input = S::parse(input)?;
+    measure(Part::One, &|| S::part_one(&input));
+    measure(Part::Two, &|| S::part_two(&input));
+    Ok(())
+}
+
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse::parse_list;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input = Vec<i64>;
+
+        fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+            parse_list(input, ",")
+        }
+
+        fn part_one(input: &Vec<i64>) -> Answer {
+            input.iter().sum::<i64>().into()
+        }
+
+        fn part_two(input: &Vec<i64>) -> Answer {
+            format!("{} numbers", input.len()).into()
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let answers = solve::<Sum>("1,2,3").unwrap();
+
+        assert_eq!(Answer::from(6), answers.part_one);
+        assert_eq!(Answer::from("3 numbers"), answers.part_two);
+        assert_eq!(&answers.part_two, answers.get(Part::Two));
+    }
+
+    #[test]
+    fn test_solve_timed() {
+        let solved = solve_timed::<Sum>("1,2,3").unwrap();
+
+        assert_eq!(Answer::from(6), solved.answers.part_one);
+        assert_eq!(solved.timings.parse + solved.timings.part_one + solved.timings.part_two, solved.timings.total());
+        assert_eq!(solved.timings.part_two, solved.timings.get(Part::Two));
+    }
+
+    #[test]
+    fn test_measure_parts() {
+        let mut measured = vec![];
+        measure_parts::<Sum>("1,2,3", &mut |part, run| measured.push((part, run(), run()))).unwrap();
+
+        assert_eq!(vec![
+            (Part::One, Answer::from(6), Answer::from(6)),
+            (Part::Two, Answer::from("3 numbers"), Answer::from("3 numbers")),
+        ], measured);
+    }
+
+    #[test]
+    fn test_solve_parse_error() {
+        let error = solve::<Sum>("1,2,three").unwrap_err();
+
+        assert_eq!("Expected i64 at line 1, column 5, got 'three'", error.to_string());
+    }
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!("42", Answer::from(42_usize).to_string());
+        assert_eq!("-7", Answer::from(-7_i64).to_string());
+    }
+}
@@ -0,0 +1,999 @@
+//! Day 3's wires: paths of moves from a central port, where they cross each
+//! other and how many steps they take to get there.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use geometry::{Direction8, Point, Segment, Vector, segment_intersections};
+use parse::ParseError;
+
+pub mod svg;
+
+pub const START_POINT: Point = Point { x: 0, y: 0 };
+
+/// A wire's path, as the moves it makes from the central port.
+#[derive(Debug, PartialEq)]
+pub struct Wire {
+    pub moves: Vec<Move>,
+}
+
+impl Wire {
+    /// A wire from a comma separated path like `R8,U5,L5,D3`.
+    pub fn parse(path: &str) -> Result<Wire, ParseError> {
+        Ok(Wire { moves: get_all_moves(&path.split(",").collect())? })
+    }
+
+    /// Steps the wire takes to first reach `point`, if it ever does.
+    pub fn signal_delay(&self, point: &Point) -> Option<i64> {
+        let mut delay = 0;
+        for m in &self.moves {
+            if let Some(distance) = m.intersects(point) {
+                return Some(delay + distance);
+            }
+            delay += m.distance();
+        }
+        None
+    }
+
+    pub fn step_index(&self) -> StepIndex {
+        StepIndex::new(&self.moves)
+    }
+
+    /// Every point the wire comes back to, ordered by point.
+    pub fn self_crossings(&self) -> Vec<SelfCrossing> {
+        let segments: Vec<Segment> = self.moves.iter().map(Move::segment).collect();
+        let mut points = BTreeSet::new();
+        for (a, b, _) in segment_intersections(&segments) {
+            let mut shared = self.moves[a].find_intersection(&self.moves[b]);
+            if b == a + 1 {
+                // One move always ends where the next starts.
+                shared.retain(|point| point != &self.moves[a].end);
+            }
+            points.extend(shared);
+        }
+
+        let index = self.step_index();
+        points.into_iter()
+            .map(|point| SelfCrossing { visits: index.visits(&point), point })
+            .filter(|crossing| crossing.visits.len() > 1)
+            .collect()
+    }
+
+    /// The wire with its loops cut out. Following it from the central port,
+    /// whenever it's at a point it comes back to later, it skips straight to
+    /// the last time it's there, so no point is visited twice.
+    pub fn shortcut(&self) -> Wire {
+        let index = self.step_index();
+        let last_visits: HashMap<Point, i64> = self.self_crossings().into_iter()
+            .map(|crossing| (crossing.point, crossing.visits[crossing.visits.len() - 1]))
+            .collect();
+
+        let mut path = vec![];
+        let mut step = 0;
+        loop {
+            let point = index.point_at(step).expect("Steps stay within the wire");
+            step = *last_visits.get(&point).unwrap_or(&step);
+            path.push(point);
+            if step == index.steps() {
+                break;
+            }
+            step += 1;
+        }
+        Wire { moves: moves_through(&path) }
+    }
+}
+
+/// A point a wire comes back to, and the steps at which it's there.
+#[derive(Debug, PartialEq)]
+pub struct SelfCrossing {
+    pub point: Point,
+    pub visits: Vec<i64>,
+}
+
+impl SelfCrossing {
+    /// The length of each loop from the point back to itself.
+    pub fn loop_lengths(&self) -> Vec<i64> {
+        self.visits.windows(2).map(|visits| visits[1] - visits[0]).collect()
+    }
+}
+
+// The moves going through each point in turn, one step apart.
+fn moves_through(path: &[Point]) -> Vec<Move> {
+    let mut moves: Vec<Move> = vec![];
+    for pair in path.windows(2) {
+        let step = pair[1] - pair[0];
+        match moves.last_mut() {
+            Some(last) if last.segment().step() == step => last.end = pair[1],
+            _ => moves.push(Move { start: pair[0], end: pair[1], move_type: move_type_of(step) }),
+        }
+    }
+    moves
+}
+
+/// Answers where a wire is after a number of steps, and at which steps it's
+/// at a point, without walking the wire for every question. Moves are
+/// indexed by the row, column or diagonal they run along, so finding a point
+/// only looks at the moves on its lines.
+#[derive(Debug)]
+pub struct StepIndex {
+    segments: Vec<Segment>,
+    /// The steps taken before each move starts.
+    starts: Vec<i64>,
+    lines: HashMap<(MoveType, i64), Vec<usize>>,
+}
+
+impl StepIndex {
+    pub fn new(moves: &[Move]) -> StepIndex {
+        let mut index = StepIndex { segments: vec![], starts: vec![], lines: HashMap::new() };
+        let mut steps = 0;
+        for (number, m) in moves.iter().enumerate() {
+            index.segments.push(m.segment());
+            index.starts.push(steps);
+            index.lines.entry((m.move_type, line_key(m.move_type, &m.start))).or_default().push(number);
+            steps += m.distance();
+        }
+        index
+    }
+
+    /// How many steps the whole wire takes.
+    pub fn steps(&self) -> i64 {
+        match self.segments.last() {
+            Some(last) => self.starts[self.starts.len() - 1] + last.length(),
+            None => 0,
+        }
+    }
+
+    /// Where the wire is after `step` steps, if it's that long.
+    pub fn point_at(&self, step: i64) -> Option<Point> {
+        if step < 0 || step > self.steps() {
+            return None;
+        }
+        if self.segments.is_empty() {
+            return Some(START_POINT);
+        }
+        let number = self.starts.partition_point(|&start| start <= step) - 1;
+        let segment = &self.segments[number];
+        Some(segment.start() + segment.step() * (step - self.starts[number]))
+    }
+
+    /// Every step at which the wire is at `point`, in order.
+    pub fn visits(&self, point: &Point) -> Vec<i64> {
+        let mut moves: Vec<usize> = [MoveType::Horizontal, MoveType::Vertical, MoveType::Diagonal, MoveType::AntiDiagonal].iter()
+            .filter_map(|&move_type| self.lines.get(&(move_type, line_key(move_type, point))))
+            .flatten()
+            .cloned()
+            .collect();
+        moves.sort();
+
+        let mut visits: Vec<i64> = moves.into_iter()
+            .filter_map(|number| self.segments[number].distance_along(point).map(|distance| self.starts[number] + distance))
+            .collect();
+        if point == &START_POINT {
+            visits.push(0);
+        }
+        visits.sort();
+        // Corners are both the end of one move and the start of the next.
+        visits.dedup();
+        visits
+    }
+
+    /// The first step at which the wire is at `point`, its signal delay.
+    pub fn first_visit(&self, point: &Point) -> Option<i64> {
+        self.visits(point).first().cloned()
+    }
+}
+
+// What stays the same along a move: its row, its column, or which diagonal
+// it's on.
+fn line_key(move_type: MoveType, point: &Point) -> i64 {
+    match move_type {
+        MoveType::Horizontal => point.y,
+        MoveType::Vertical => point.x,
+        MoveType::Diagonal => point.y - point.x,
+        MoveType::AntiDiagonal => point.y + point.x,
+    }
+}
+
+/// Parses each non-empty line of `input` as a wire.
+pub fn parse_wires(input: &str) -> Result<Vec<Wire>, ParseError> {
+    input.lines().enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| Wire::parse(line).map_err(|error| ParseError { line: index + 1, ..error }))
+        .collect()
+}
+
+/// A point, other than the central port, where more than one wire meets.
+#[derive(Debug, PartialEq)]
+pub struct Crossing {
+    pub point: Point,
+    /// The indices of the wires meeting there, in order.
+    pub wires: Vec<usize>,
+}
+
+impl Crossing {
+    /// The steps every wire meeting here takes to first reach it, added up.
+    pub fn combined_signal_delay(&self, wires: &[Wire]) -> i64 {
+        self.wires.iter()
+            .map(|&wire| wires[wire].signal_delay(&self.point).expect("Every wire of a crossing reaches it"))
+            .sum()
+    }
+}
+
+/// Every point where two or more of `wires` meet, ordered by point.
+pub fn find_crossings(wires: &[Wire]) -> Vec<Crossing> {
+    let mut owners = vec![];
+    let mut segments = vec![];
+    for (index, wire) in wires.iter().enumerate() {
+        for m in &wire.moves {
+            owners.push(index);
+            segments.push(m.segment());
+        }
+    }
+
+    let mut crossings: BTreeMap<Point, BTreeSet<usize>> = BTreeMap::new();
+    for (a, b, overlap) in segment_intersections(&segments) {
+        if owners[a] == owners[b] {
+            continue;
+        }
+        for point in overlap.points().into_iter().filter(|point| point != &START_POINT) {
+            let meeting = crossings.entry(point).or_default();
+            meeting.insert(owners[a]);
+            meeting.insert(owners[b]);
+        }
+    }
+
+    crossings.into_iter()
+        .map(|(point, meeting)| Crossing { point, wires: meeting.into_iter().collect() })
+        .collect()
+}
+
+/// Points where at least `k` different wires meet.
+pub fn crossed_by_at_least(wires: &[Wire], k: usize) -> Vec<Point> {
+    find_crossings(wires).into_iter()
+        .filter(|crossing| crossing.wires.len() >= k)
+        .map(|crossing| crossing.point)
+        .collect()
+}
+
+/// For each pair of wires that meet, the crossing closest to the central port.
+pub fn closest_intersections(wires: &[Wire]) -> BTreeMap<(usize, usize), Point> {
+    let mut closest: BTreeMap<(usize, usize), Point> = BTreeMap::new();
+    for crossing in find_crossings(wires) {
+        let distance = crossing.point.manhattan_distance(&START_POINT);
+        for (index, &first) in crossing.wires.iter().enumerate() {
+            for &second in &crossing.wires[index + 1..] {
+                let best = closest.entry((first, second)).or_insert(crossing.point);
+                if distance < best.manhattan_distance(&START_POINT) {
+                    *best = crossing.point;
+                }
+            }
+        }
+    }
+    closest
+}
+
+/// The Manhattan distance to the central port of the closest crossing.
+pub fn closest_crossing_distance(wires: &[Wire]) -> Option<i64> {
+    find_crossings(wires).iter().map(|crossing| crossing.point.manhattan_distance(&START_POINT)).min()
+}
+
+/// The combined signal delay of each of `crossings`, indexing each wire once
+/// rather than walking it for every crossing.
+pub fn combined_signal_delays(wires: &[Wire], crossings: &[Crossing]) -> Vec<i64> {
+    let indices: Vec<StepIndex> = wires.iter().map(Wire::step_index).collect();
+    crossings.iter()
+        .map(|crossing| crossing.wires.iter()
+            .map(|&wire| indices[wire].first_visit(&crossing.point).expect("Every wire of a crossing reaches it"))
+            .sum())
+        .collect()
+}
+
+/// The lowest combined signal delay of any crossing.
+pub fn lowest_combined_signal_delay(wires: &[Wire]) -> Option<i64> {
+    combined_signal_delays(wires, &find_crossings(wires)).into_iter().min()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Move {
+    pub start: Point,
+    pub end: Point,
+    pub move_type: MoveType,
+}
+
+/// Which way a move runs: along an axis, rising to the right like `NE` and
+/// `SW`, or falling to the right like `SE` and `NW`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveType {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+const EXPECTED_DIRECTION: &str = "a move starting with U, D, L, R, N, NE, E, SE, S, SW, W or NW";
+
+/// The moves of a path split on commas, like `R8`, `U5` or `NE12`.
+pub fn get_all_moves(moves_text: &Vec<&str>) -> Result<Vec<Move>, ParseError> {
+    let path = moves_text.join(",");
+    let mut offset = 0;
+    let mut start = START_POINT;
+    let mut moves = vec![];
+
+    for move_text in moves_text {
+        let position = offset + move_text.len() - move_text.trim_start().len();
+        offset += move_text.len() + 1;
+
+        let move_text = move_text.trim();
+        let direction = get_direction(move_text)
+            .ok_or_else(|| ParseError::at(&path, position, move_text, EXPECTED_DIRECTION))?;
+        let steps = split_move(move_text).1.parse::<i64>()
+            .map_err(|_| ParseError::at(&path, position, move_text, "a number of steps after the direction"))?;
+        let end = start + direction.vector() * steps;
+
+        moves.push(Move { start, end, move_type: move_type_of(direction.vector()) });
+        start = end;
+    }
+
+    Ok(moves)
+}
+
+// Splits a move like `NE12` into its direction and its number of steps.
+fn split_move(move_text: &str) -> (&str, &str) {
+    let digits = move_text.find(|c: char| c.is_ascii_digit()).unwrap_or(move_text.len());
+    move_text.split_at(digits)
+}
+
+pub fn get_direction(move_text: &str) -> Option<Direction8> {
+    Direction8::from_name(split_move(move_text).0)
+}
+
+pub fn get_move_type(move_text: &str) -> Option<MoveType> {
+    Some(move_type_of(get_direction(move_text)?.vector()))
+}
+
+fn move_type_of(step: Vector) -> MoveType {
+    match (step.x, step.y) {
+        (_, 0) => MoveType::Horizontal,
+        (0, _) => MoveType::Vertical,
+        (x, y) if x == y => MoveType::Diagonal,
+        _ => MoveType::AntiDiagonal,
+    }
+}
+
+/// How far a move goes along its `MoveType`, negative when it goes left, or
+/// down for vertical moves.
+pub fn get_moves_distance(move_text: &str) -> Option<i64> {
+    let vector = get_direction(move_text)?.vector::<i64>();
+    let sign = if vector.x != 0 { vector.x } else { vector.y };
+    let steps = split_move(move_text).1.parse::<i64>().ok()?;
+    Some(sign * steps)
+}
+
+pub trait MoveIntersections {
+    fn find_intersections(&self, other: &[Move]) -> Vec<Point>;
+}
+impl MoveIntersections for [Move] {
+    /// Points where this wire meets `other`, ordered by this wire's moves.
+    fn find_intersections(&self, other: &[Move]) -> Vec<Point> {
+        let segments: Vec<Segment> = self.iter().chain(other).map(|m| m.segment()).collect();
+
+        segment_intersections(&segments)
+        .into_iter()
+        .filter(|&(a, b, _)| a < self.len() && b >= self.len())
+        .flat_map(|(_, _, overlap)| overlap.points())
+        .filter(|i| i != &START_POINT)
+        .collect()
+    }
+}
+
+impl Move {
+    pub fn segment(&self) -> Segment {
+        Segment::new(self.start, self.end).expect("Moves run along an axis or a diagonal")
+    }
+
+    /// Every point both moves pass through: one where they cross, or all of
+    /// the points they share when they run along the same line.
+    pub fn find_intersection(&self, other: &Move) -> Vec<Point> {
+        match self.segment().intersection(&other.segment()) {
+            Some(overlap) => overlap.points(),
+            None => vec![],
+        }
+    }
+
+    pub fn intersects(&self, point: &Point) -> Option<i64> {
+        self.segment().distance_along(point)
+    }
+
+    pub fn distance(&self) -> i64 {
+        self.segment().length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use read_inputs;
+
+    use std::time::Instant;
+
+    // Every move of one wire against every move of the other, which the
+    // sweep has to agree with.
+    fn find_intersections_pairwise(first_moves: &Vec<Move>, second_moves: &Vec<Move>) -> Vec<Point> {
+        let mut intersections = vec![];
+        for m in first_moves {
+            for other in second_moves {
+                intersections.extend(m.find_intersection(other));
+            }
+        }
+        intersections.into_iter().filter(|i| i != &START_POINT).collect()
+    }
+
+    fn get_closest_intersection(first_line: &str, second_line: &str) -> i64 {
+        closest_crossing_distance(&[Wire::parse(first_line).unwrap(), Wire::parse(second_line).unwrap()]).unwrap()
+    }
+
+    fn get_minimum_signal_delay_intersection(first_line: &str, second_line: &str) -> i64 {
+        lowest_combined_signal_delay(&[Wire::parse(first_line).unwrap(), Wire::parse(second_line).unwrap()]).unwrap()
+    }
+
+    fn sorted(mut points: Vec<Point>) -> Vec<Point> {
+        points.sort();
+        points
+    }
+
+    #[test]
+    fn test_point_equal() {
+        assert_eq!(Point { x: 99, y: 98 }, Point { x: 99, y: 98 });
+        assert_ne!(Point { x: 99, y: 98 }, Point { x: 99, y: 99 });
+    }
+    #[test]
+    fn test_point_clone() {
+        let point = Point { x: 99, y: 98 };
+        assert_eq!(point, point.clone());
+    }
+    #[test]
+    fn test_get_all_moves_single_move() {
+        let moves_representation = vec!["R10"];
+        let expected_moves = vec![Move {
+            start: START_POINT,
+            end: Point { x: 10, y: 0 },
+            move_type: MoveType::Horizontal,
+        }];
+
+        assert_eq!(expected_moves, get_all_moves(&moves_representation).unwrap());
+    }
+
+    #[test]
+    fn test_get_all_moves_multiple_moves() {
+        let moves_representation = vec!["R10", "U50", "L20", "D100", "R10", "U50"];
+        let expected_moves = vec![
+            Move {
+                start: START_POINT,
+                end: Point { x: 10, y: 0 },
+                move_type: MoveType::Horizontal,
+            },
+            Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+            },
+            Move {
+                start: Point { x: 10, y: 50 },
+                end: Point { x: -10, y: 50 },
+                move_type: MoveType::Horizontal,
+            },
+            Move {
+                start: Point { x: -10, y: 50 },
+                end: Point { x: -10, y: -50 },
+                move_type: MoveType::Vertical,
+            },
+            Move {
+                start: Point { x: -10, y: -50 },
+                end: Point { x: 0, y: -50 },
+                move_type: MoveType::Horizontal,
+            },
+            Move {
+                start: Point { x: 0, y: -50 },
+                end: START_POINT,
+                move_type: MoveType::Vertical,
+            },
+        ];
+
+        assert_eq!(expected_moves, get_all_moves(&moves_representation).unwrap());
+    }
+
+    #[test]
+    fn test_get_move_type() {
+        assert_eq!(Some(MoveType::Horizontal), get_move_type("R10"));
+        assert_eq!(Some(MoveType::Horizontal), get_move_type("L10"));
+        assert_eq!(Some(MoveType::Vertical), get_move_type("U10"));
+        assert_eq!(Some(MoveType::Vertical), get_move_type("D10"));
+        assert_eq!(Some(MoveType::Diagonal), get_move_type("NE10"));
+        assert_eq!(Some(MoveType::Diagonal), get_move_type("SW10"));
+        assert_eq!(Some(MoveType::AntiDiagonal), get_move_type("NW10"));
+        assert_eq!(Some(MoveType::AntiDiagonal), get_move_type("SE10"));
+        assert_eq!(None, get_move_type("X10"));
+        assert_eq!(None, get_move_type("NNE10"));
+    }
+
+    #[test]
+    fn test_get_move_distance() {
+        assert_eq!(Some(10), get_moves_distance("R10"));
+        assert_eq!(Some(-999), get_moves_distance("L999"));
+        assert_eq!(Some(5), get_moves_distance("U5"));
+        assert_eq!(Some(-10000), get_moves_distance("D10000"));
+        assert_eq!(Some(3), get_moves_distance("SE3"));
+        assert_eq!(Some(-3), get_moves_distance("NW3"));
+        assert_eq!(None, get_moves_distance("R"));
+        assert_eq!(None, get_moves_distance("Q3"));
+    }
+
+    #[test]
+    fn test_get_all_moves_errors() {
+        assert_eq!(
+            "Expected a move starting with U, D, L, R, N, NE, E, SE, S, SW, W or NW at line 1, column 4, got 'X5'",
+            get_all_moves(&vec!["R8", "X5", "L5"]).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected a number of steps after the direction at line 1, column 5, got 'U'",
+            Wire::parse("R8, U").unwrap_err().to_string()
+        );
+        assert_eq!(3, parse_wires("R8\n\nNE2,Z1\n").unwrap_err().line);
+    }
+
+    #[test]
+    fn test_get_all_moves_ends() {
+        let ends: Vec<Point> = get_all_moves(&vec!["L10", "U10", "SW10", "SE10", "NE5", "NW5", "E3", "S3"]).unwrap()
+            .iter().map(|m| m.end).collect();
+
+        assert_eq!(vec![
+            Point { x: -10, y: 0 },
+            Point { x: -10, y: 10 },
+            Point { x: -20, y: 0 },
+            Point { x: -10, y: -10 },
+            Point { x: -5, y: -5 },
+            Point { x: -10, y: 0 },
+            Point { x: -7, y: 0 },
+            Point { x: -7, y: -3 },
+        ], ends);
+    }
+
+    #[test]
+    fn test_find_move_intersection_diagonal() {
+        let first_move = Move {
+                start: Point { x: 0, y: 0 },
+                end: Point { x: 10, y: 10 },
+                move_type: MoveType::Diagonal,
+        };
+        let crossing = Move {
+                start: Point { x: 0, y: 8 },
+                end: Point { x: 8, y: 0 },
+                move_type: MoveType::AntiDiagonal,
+        };
+        let between_points = Move {
+                start: Point { x: 0, y: 7 },
+                end: Point { x: 7, y: 0 },
+                move_type: MoveType::AntiDiagonal,
+        };
+
+        assert_eq!(vec![Point { x: 4, y: 4 }], first_move.find_intersection(&crossing));
+        assert_eq!(Vec::<Point>::new(), first_move.find_intersection(&between_points));
+        assert_eq!(10, first_move.distance());
+        assert_eq!(Some(4), first_move.intersects(&Point { x: 4, y: 4 }));
+    }
+
+    #[test]
+    fn test_find_intersections() {
+        let first_moves = [
+            Move {
+                start: START_POINT,
+                end: Point { x: 10, y: 0 },
+                move_type: MoveType::Horizontal,
+            },
+            Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+            },
+            Move {
+                start: Point { x: 10, y: 50 },
+                end: Point { x: 20, y: 50 },
+                move_type: MoveType::Horizontal,
+            },
+            Move {
+                start: Point { x: 20, y: 50 },
+                end: Point { x: 20, y: 0 },
+                move_type: MoveType::Vertical,
+            },
+        ];
+
+        let second_moves = vec![
+            Move{
+                start: Point { x: 25, y: 25},
+                end: Point { x:-25, y: 25},
+                move_type: MoveType::Horizontal
+            }
+        ];
+
+        let expected_intersections = vec![Point{x: 10, y: 25}, Point{x: 20, y: 25}];
+
+        assert_eq!(expected_intersections, first_moves.find_intersections(&second_moves));
+    }
+
+    #[test]
+    fn test_find_move_intersection() {
+        let first_move = Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+        };
+        let second_move = Move {
+                start: Point { x: 25, y: 25 },
+                end: Point { x: -25, y: 25 },
+                move_type: MoveType::Horizontal,
+        };
+
+        let expected_intersection = vec![Point{x: 10, y: 25}];
+
+        assert_eq!(expected_intersection, first_move.find_intersection(&second_move));
+    }
+
+    #[test]
+    fn test_find_move_intersection_none_found() {
+        let first_move = Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+        };
+        let second_move = Move {
+                start: Point { x: 25, y: 25 },
+                end: Point { x: 25, y: 26 },
+                move_type: MoveType::Vertical,
+        };
+
+        assert_eq!(Vec::<Point>::new(), first_move.find_intersection(&second_move));
+    }
+
+    #[test]
+    fn test_find_move_intersection_collinear() {
+        let first_move = Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+        };
+        let second_move = Move {
+                start: Point { x: 10, y: 53 },
+                end: Point { x: 10, y: 47 },
+                move_type: MoveType::Vertical,
+        };
+
+        let expected_intersections = vec![Point{x: 10, y: 47}, Point{x: 10, y: 48}, Point{x: 10, y: 49}, Point{x: 10, y: 50}];
+
+        assert_eq!(expected_intersections, first_move.find_intersection(&second_move));
+    }
+
+    #[test]
+    fn test_find_move_intersection_collinear_apart() {
+        let first_move = Move {
+                start: Point { x: 0, y: 7 },
+                end: Point { x: 10, y: 7 },
+                move_type: MoveType::Horizontal,
+        };
+        let second_move = Move {
+                start: Point { x: 11, y: 7 },
+                end: Point { x: 20, y: 7 },
+                move_type: MoveType::Horizontal,
+        };
+
+        assert_eq!(Vec::<Point>::new(), first_move.find_intersection(&second_move));
+    }
+
+    #[test]
+    fn test_get_distance() {
+         let first_move = Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+        };
+        assert_eq!(50, first_move.distance());
+    }
+
+    #[test]
+    fn test_intersects() {
+        let first_move = Move {
+                start: Point { x: 10, y: 0 },
+                end: Point { x: 10, y: 50 },
+                move_type: MoveType::Vertical,
+        };
+        let valid_point = Point { x: 10, y: 25 };
+        let invalid_point = Point { x: 15, y: 25 };
+        let beyond_the_end = Point { x: 10, y: 60 };
+
+        assert_eq!(Some(25), first_move.intersects(&valid_point));
+        assert_eq!(None, first_move.intersects(&invalid_point));
+        assert_eq!(None, first_move.intersects(&beyond_the_end));
+    }
+
+    #[test]
+    fn test_manhattan_distance() {
+        let first_point = Point{x: 1, y: 2};
+        let second_point = Point{x: 3, y: 4};
+        assert_eq!(4, first_point.manhattan_distance(&second_point));
+    }
+
+    #[test]
+    fn test_manhattan_distance_negative() {
+        let first_point = START_POINT;
+        let second_point = Point{x: 158, y: -12};
+        assert_eq!(170, first_point.manhattan_distance(&second_point));
+    }
+
+
+    #[test]
+    fn test_manhattan_first_test_case() {
+        let first_line = "R75,D30,R83,U83,L12,D49,R71,U7,L72";
+        let second_line = "U62,R66,U55,R34,D71,R55,D58,R83";
+
+        assert_eq!(159, get_closest_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_manhattan_second_test_case() {
+        let first_line = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51";
+        let second_line = "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
+
+        assert_eq!(135, get_closest_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_manhattan_first_example() {
+        let first_line = "R8,U5,L5,D3";
+        let second_line = "U7,R6,D4,L4";
+
+        assert_eq!(6, get_closest_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_signal_delay() {
+        let moves = vec!["R8", "U5", "L5", "D3"];
+        let moves = get_all_moves(&moves).unwrap();
+        let point = Point{x: 3, y: 3};
+        assert_eq!(Some(20), Wire { moves }.signal_delay(&point));
+    }
+
+    #[test]
+    fn test_signal_delay_skips_moves_on_the_same_line() {
+        // The first move runs along y = 0 without reaching x = 7, so the
+        // delay has to come from the last move.
+        let moves = get_all_moves(&vec!["R5", "U2", "R5", "D2", "L3"]).unwrap();
+        assert_eq!(Some(17), Wire { moves }.signal_delay(&Point{x: 7, y: 0}));
+    }
+
+    #[test]
+    fn test_collinear_wires() {
+        let first_line = "R10,U5";
+        let second_line = "L2,R9,U3";
+
+        assert_eq!(1, get_closest_intersection(first_line, second_line));
+        assert_eq!(6, get_minimum_signal_delay_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_delay_first_example() {
+        let first_line = "R8,U5,L5,D3";
+        let second_line = "U7,R6,D4,L4";
+
+        assert_eq!(30, get_minimum_signal_delay_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_delay_first_test_case() {
+        let first_line = "R75,D30,R83,U83,L12,D49,R71,U7,L72";
+        let second_line = "U62,R66,U55,R34,D71,R55,D58,R83";
+
+        assert_eq!(610, get_minimum_signal_delay_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_delay_second_test_case() {
+        let first_line = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51";
+        let second_line = "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
+
+        assert_eq!(410, get_minimum_signal_delay_intersection(first_line, second_line));
+    }
+
+    #[test]
+    fn test_find_intersections_agrees_with_pairwise() {
+        let first_moves = get_all_moves(&vec!["R75", "D30", "R83", "U83", "L12", "D49", "R71", "U7", "L72", "D12", "L30"]).unwrap();
+        let second_moves = get_all_moves(&vec!["U62", "R66", "U55", "R34", "D71", "R55", "D58", "R83", "L200"]).unwrap();
+
+        assert_eq!(
+            sorted(find_intersections_pairwise(&first_moves, &second_moves)),
+            sorted(first_moves.find_intersections(&second_moves))
+        );
+    }
+
+    // Compares the sweep with trying every pair of moves on the real input:
+    // cargo test --release bench_find_intersections -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_find_intersections() {
+        let input = read_inputs("inputs/day3.txt").unwrap();
+        let mut wires = input.lines().map(|line| Wire::parse(line).unwrap());
+        let first_moves = wires.next().unwrap().moves;
+        let second_moves = wires.next().unwrap().moves;
+        let runs = 20;
+
+        let start = Instant::now();
+        for _ in 0..runs {
+            first_moves.find_intersections(&second_moves);
+        }
+        let sweep = start.elapsed() / runs;
+
+        let start = Instant::now();
+        for _ in 0..runs {
+            find_intersections_pairwise(&first_moves, &second_moves);
+        }
+        let pairwise = start.elapsed() / runs;
+
+        println!("{} and {} moves: sweep {:?}, pairwise {:?}", first_moves.len(), second_moves.len(), sweep, pairwise);
+        assert_eq!(
+            sorted(find_intersections_pairwise(&first_moves, &second_moves)),
+            sorted(first_moves.find_intersections(&second_moves))
+        );
+    }
+
+    // Wire 0 runs along y = 0, wire 1 crosses it at (5, 0) and wire 2 runs
+    // alongside both for a while.
+    fn three_wires() -> Vec<Wire> {
+        vec![Wire::parse("R10").unwrap(), Wire::parse("U5,R5,D10").unwrap(), Wire::parse("R5,U10").unwrap()]
+    }
+
+    #[test]
+    fn test_find_crossings() {
+        let crossings = find_crossings(&three_wires());
+
+        assert_eq!(10, crossings.len());
+        assert_eq!(Crossing { point: Point { x: 1, y: 0 }, wires: vec![0, 2] }, crossings[0]);
+        assert_eq!(Crossing { point: Point { x: 5, y: 0 }, wires: vec![0, 1, 2] }, crossings[4]);
+        assert_eq!(Crossing { point: Point { x: 5, y: 5 }, wires: vec![1, 2] }, crossings[9]);
+    }
+
+    #[test]
+    fn test_crossed_by_at_least() {
+        assert_eq!(vec![Point { x: 5, y: 0 }], crossed_by_at_least(&three_wires(), 3));
+        assert_eq!(10, crossed_by_at_least(&three_wires(), 2).len());
+        assert!(crossed_by_at_least(&three_wires(), 4).is_empty());
+    }
+
+    #[test]
+    fn test_closest_intersections() {
+        let closest = closest_intersections(&three_wires());
+
+        assert_eq!(Some(&Point { x: 5, y: 0 }), closest.get(&(0, 1)));
+        assert_eq!(Some(&Point { x: 1, y: 0 }), closest.get(&(0, 2)));
+        assert_eq!(Some(&Point { x: 5, y: 0 }), closest.get(&(1, 2)));
+        assert_eq!(3, closest.len());
+    }
+
+    #[test]
+    fn test_combined_signal_delay() {
+        let wires = three_wires();
+        let crossings = find_crossings(&wires);
+
+        assert_eq!(25, crossings[4].combined_signal_delay(&wires));
+        assert_eq!(Some(2), lowest_combined_signal_delay(&wires));
+        assert_eq!(crossings.iter().map(|crossing| crossing.combined_signal_delay(&wires)).collect::<Vec<i64>>(),
+            combined_signal_delays(&wires, &crossings));
+        assert_eq!(Some(1), closest_crossing_distance(&wires));
+        assert_eq!(Some(15), wires[1].signal_delay(&Point { x: 5, y: 0 }));
+        assert_eq!(None, wires[0].signal_delay(&Point { x: 11, y: 0 }));
+    }
+
+    #[test]
+    fn test_wires_that_never_cross() {
+        let wires = vec![Wire::parse("U5").unwrap(), Wire::parse("D5").unwrap()];
+
+        assert!(find_crossings(&wires).is_empty());
+        assert_eq!(None, closest_crossing_distance(&wires));
+    }
+
+    #[test]
+    fn test_self_crossings() {
+        let wire = Wire::parse("R4,U4,L4,D4,R2,NE2").unwrap();
+        let crossings = wire.self_crossings();
+
+        assert_eq!(
+            vec![Point { x: 0, y: 0 }, Point { x: 1, y: 0 }, Point { x: 2, y: 0 }, Point { x: 4, y: 2 }],
+            crossings.iter().map(|crossing| crossing.point).collect::<Vec<Point>>()
+        );
+        assert_eq!(vec![0, 16], crossings[0].visits);
+        assert_eq!(vec![16], crossings[0].loop_lengths());
+        assert_eq!(vec![14], crossings[3].loop_lengths());
+        assert!(Wire::parse("R8,U5,L5,D3").unwrap().self_crossings().is_empty());
+    }
+
+    #[test]
+    fn test_self_crossings_doubling_back() {
+        let crossings = Wire::parse("R5,L3,U1,D2").unwrap().self_crossings();
+
+        assert_eq!(
+            vec![
+                SelfCrossing { point: Point { x: 2, y: 0 }, visits: vec![2, 8, 10] },
+                SelfCrossing { point: Point { x: 3, y: 0 }, visits: vec![3, 7] },
+                SelfCrossing { point: Point { x: 4, y: 0 }, visits: vec![4, 6] },
+            ],
+            crossings
+        );
+        assert_eq!(vec![6, 2], crossings[0].loop_lengths());
+    }
+
+    #[test]
+    fn test_shortcut() {
+        let wire = Wire::parse("R4,U4,L4,D4,R2,NE2").unwrap();
+        let expected = Wire::parse("R2,NE2").unwrap();
+
+        assert_eq!(expected, wire.shortcut());
+        assert_eq!(4, wire.shortcut().step_index().steps());
+        assert_eq!(Wire::parse("R2,D1").unwrap(), Wire::parse("R5,L3,U1,D2").unwrap().shortcut());
+    }
+
+    #[test]
+    fn test_shortcut_without_loops() {
+        let wire = Wire::parse("R8,U5,L5,D3").unwrap();
+
+        assert_eq!(wire, wire.shortcut());
+        assert!(Wire::parse("R2,U2,L2,D2").unwrap().shortcut().moves.is_empty());
+    }
+
+    #[test]
+    fn test_shortcut_has_no_loops() {
+        let wire = Wire::parse("R75,D30,R83,U83,L12,D49,R71,U7,L72,D60,L10,U80,R40").unwrap();
+        let shortcut = wire.shortcut();
+
+        assert!(!wire.self_crossings().is_empty());
+        assert!(shortcut.self_crossings().is_empty());
+        assert_eq!(wire.moves.last().unwrap().end, shortcut.moves.last().unwrap().end);
+        assert!(shortcut.step_index().steps() < wire.step_index().steps());
+    }
+
+    #[test]
+    fn test_step_index_point_at() {
+        let index = Wire::parse("R8,U5,L5,D3").unwrap().step_index();
+
+        assert_eq!(21, index.steps());
+        assert_eq!(Some(START_POINT), index.point_at(0));
+        assert_eq!(Some(Point { x: 5, y: 0 }), index.point_at(5));
+        assert_eq!(Some(Point { x: 8, y: 0 }), index.point_at(8));
+        assert_eq!(Some(Point { x: 8, y: 2 }), index.point_at(10));
+        assert_eq!(Some(Point { x: 3, y: 2 }), index.point_at(21));
+        assert_eq!(None, index.point_at(22));
+        assert_eq!(None, index.point_at(-1));
+    }
+
+    #[test]
+    fn test_step_index_visits() {
+        // Goes round a square and back along its first side.
+        let index = Wire::parse("R4,U4,L4,D4,R2,NE2").unwrap().step_index();
+
+        assert_eq!(vec![0, 16], index.visits(&START_POINT));
+        assert_eq!(vec![2, 18], index.visits(&Point { x: 2, y: 0 }));
+        assert_eq!(vec![4], index.visits(&Point { x: 4, y: 0 }));
+        assert_eq!(vec![6, 20], index.visits(&Point { x: 4, y: 2 }));
+        assert_eq!(Some(8), index.first_visit(&Point { x: 4, y: 4 }));
+        assert_eq!(None, index.first_visit(&Point { x: 5, y: 0 }));
+        assert_eq!(Some(Point { x: 3, y: 1 }), index.point_at(19));
+    }
+
+    #[test]
+    fn test_step_index_agrees_with_signal_delay() {
+        let wire = Wire::parse("R75,D30,R83,U83,L12,D49,R71,U7,L72").unwrap();
+        let index = wire.step_index();
+
+        for step in 0..=index.steps() {
+            let point = index.point_at(step).unwrap();
+            assert_eq!(wire.signal_delay(&point).unwrap(), index.first_visit(&point).unwrap());
+            assert!(index.visits(&point).contains(&step));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! SVG drawings of wires, for seeing where they go rather than reading
+//! coordinates.
+//!
+//! Each wire is drawn in its own colour, every crossing is marked with a
+//! white dot, and the crossing closest to the central port and the one with
+//! the lowest combined signal delay are ringed in red and green. The drawing
+//! is scaled to fit the wires' bounding box into `SIZE` pixels, with `y`
+//! flipped so that up is up.
+
+use super::*;
+
+use std::fmt::Write;
+
+use geometry::BoundingBox;
+
+pub const SIZE: f64 = 800.0;
+const MARGIN: f64 = 20.0;
+const COLOURS: [&str; 6] = ["#1f77b4", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf"];
+const CLOSEST: &str = "#d62728";
+const LOWEST_DELAY: &str = "#2ca02c";
+
+pub fn render_svg(wires: &[Wire]) -> String {
+    let corners = wires.iter().flat_map(|wire| wire.moves.iter().map(|m| m.end));
+    let bounds = BoundingBox::from_points(corners).unwrap_or(BoundingBox::new(START_POINT, START_POINT)).extend(START_POINT);
+    let scale = Scale::fit(&bounds);
+    let crossings = find_crossings(wires);
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w:.0} {h:.0}" width="{w:.0}" height="{h:.0}">"#,
+        w = scale.width, h = scale.height).unwrap();
+    svg.push_str(r##"<rect width="100%" height="100%" fill="#202020"/>"##);
+    svg.push('\n');
+
+    for (index, wire) in wires.iter().enumerate() {
+        let mut points = vec![scale.project(&START_POINT)];
+        points.extend(wire.moves.iter().map(|m| scale.project(&m.end)));
+        let points: Vec<String> = points.iter().map(|&(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        writeln!(svg, r#"<polyline class="wire" points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+            points.join(" "), COLOURS[index % COLOURS.len()]).unwrap();
+    }
+
+    for crossing in &crossings {
+        marker(&mut svg, &scale, &crossing.point, 2.5, ("white", "none"), "crossing");
+    }
+    let closest = crossings.iter().min_by_key(|crossing| crossing.point.manhattan_distance(&START_POINT));
+    if let Some(crossing) = closest {
+        marker(&mut svg, &scale, &crossing.point, 7.0, ("none", CLOSEST), "closest");
+    }
+    let lowest_delay = crossings.iter().zip(combined_signal_delays(wires, &crossings)).min_by_key(|&(_, delay)| delay);
+    if let Some((crossing, _)) = lowest_delay {
+        marker(&mut svg, &scale, &crossing.point, 10.0, ("none", LOWEST_DELAY), "lowest-delay");
+    }
+    marker(&mut svg, &scale, &START_POINT, 4.0, ("black", "none"), "port");
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Highlights are unfilled rings, so that markers on the same point all stay
+// visible.
+fn marker(svg: &mut String, scale: &Scale, point: &Point, radius: f64, (fill, stroke): (&str, &str), class: &str) {
+    let (x, y) = scale.project(point);
+    writeln!(svg, r#"<circle class="{}" cx="{:.1}" cy="{:.1}" r="{}" fill="{}" stroke="{}" stroke-width="2"><title>({}, {})</title></circle>"#,
+        class, x, y, radius, fill, stroke, point.x, point.y).unwrap();
+}
+
+// Maps puzzle coordinates onto the drawing.
+struct Scale {
+    bounds: BoundingBox,
+    factor: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Scale {
+    fn fit(bounds: &BoundingBox) -> Scale {
+        let (width, height) = (bounds.max.x - bounds.min.x, bounds.max.y - bounds.min.y);
+        let factor = SIZE / width.max(height).max(1) as f64;
+        Scale {
+            bounds: *bounds,
+            factor,
+            width: width as f64 * factor + 2.0 * MARGIN,
+            height: height as f64 * factor + 2.0 * MARGIN,
+        }
+    }
+
+    fn project(&self, point: &Point) -> (f64, f64) {
+        let x = (point.x - self.bounds.min.x) as f64 * self.factor + MARGIN;
+        let y = (self.bounds.max.y - point.y) as f64 * self.factor + MARGIN;
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Wire> {
+        vec![Wire::parse("R8,U5,L5,D3").unwrap(), Wire::parse("U7,R6,D4,L4").unwrap()]
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg(&example());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(2, svg.matches("class=\"wire\"").count());
+        assert_eq!(2, svg.matches("class=\"crossing\"").count());
+        assert!(svg.contains(&format!("stroke=\"{}\"", COLOURS[0])));
+        assert!(svg.contains(&format!("stroke=\"{}\"", COLOURS[1])));
+    }
+
+    #[test]
+    fn test_render_svg_highlights() {
+        let svg = render_svg(&example());
+        let closest = svg.lines().find(|line| line.contains("class=\"closest\"")).unwrap();
+        let lowest_delay = svg.lines().find(|line| line.contains("class=\"lowest-delay\"")).unwrap();
+
+        assert!(closest.contains("<title>(3, 3)</title>"));
+        assert!(lowest_delay.contains("<title>(6, 5)</title>"));
+    }
+
+    #[test]
+    fn test_scale_fits_bounding_box() {
+        let bounds = BoundingBox::new(Point { x: -100, y: -20 }, Point { x: 300, y: 180 });
+        let scale = Scale::fit(&bounds);
+
+        assert_eq!(2.0, scale.factor);
+        assert_eq!((MARGIN, MARGIN), scale.project(&Point { x: -100, y: 180 }));
+        assert_eq!((SIZE + MARGIN, 0.5 * SIZE + MARGIN), scale.project(&Point { x: 300, y: -20 }));
+        assert_eq!((SIZE + 2.0 * MARGIN, 0.5 * SIZE + 2.0 * MARGIN), (scale.width, scale.height));
+    }
+
+    #[test]
+    fn test_render_svg_without_crossings() {
+        let svg = render_svg(&[Wire::parse("U5").unwrap()]);
+
+        assert_eq!(1, svg.matches("class=\"wire\"").count());
+        assert!(!svg.contains("class=\"closest\""));
+        assert!(svg.contains("class=\"port\""));
+    }
+}
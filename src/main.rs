@@ -1,25 +1,194 @@
+extern crate advent;
+extern crate ansi_term;
+use advent::*;
+
+pub mod answers;
+pub mod bench;
 pub mod days;
-use days::*;
+pub mod options;
+pub mod report;
+pub mod runner;
+pub mod scaffold;
+use answers::{Check, RecordedAnswers};
+use options::{Format, Mode};
 use std::env;
+use std::path::Path;
+use std::process;
+
+#[global_allocator]
+static ALLOCATOR: bench::CountingAllocator = bench::CountingAllocator;
 
 fn main() {
-    match get_day() {
-        1 => day1::run(),
-        2 => day2::run(),
-        3 => day3::run(),
-        4 => day4::run(),
-        5 => day5::run(),
-        6 => day6::run(),
-        8 => day8::run(),
-        _ => println!("Unknown day - {}", get_day())
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let options = match options::parse_options(&arguments) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, options::USAGE);
+            process::exit(1);
+        }
+    };
+
+    if options.command == "list" {
+        list_days();
+        return;
+    }
+
+    if options.command == "new" {
+        new_day(options.argument.as_ref().expect("new always has an argument"));
+        return;
+    }
+
+    if options.command == "bench" {
+        bench_days(&options);
+        return;
+    }
+
+    let selected = match runner::select_days(&options.command) {
+        Ok(selected) => selected,
+        Err(error) => {
+            println!("{}", error);
+            list_days();
+            return;
+        }
+    };
+
+    if selected.len() > 1 && options.input != options::Input::Default {
+        eprintln!("--input can only be used when running a single day");
+        process::exit(1);
+    }
+
+    // A missing answers file is only an error when checking against it.
+    let mut recorded = match options.mode {
+        Mode::Record if !Path::new(&options.answers_file).exists() => RecordedAnswers::default(),
+        Mode::Check | Mode::Record => RecordedAnswers::load(&options.answers_file).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        }),
+        Mode::Run => RecordedAnswers::default(),
+    };
+
+    // JSON output keeps stdout to the records, so anything else goes to stderr.
+    let human = options.format == Format::Human;
+    let note = |message: String| if human { println!("{}", message) } else { eprintln!("{}", message) };
+
+    let mut runs = vec![];
+    let mut failed = false;
+    let mut checks = vec![];
+    for day in &selected {
+        if human && selected.len() > 1 {
+            println!("Day {}", day.number);
+        }
+        match runner::run_day(day, &options.input) {
+            Ok(run) => {
+                for part in &Part::all() {
+                    let answer = run.solved.answers.get(*part);
+                    let check = match options.mode {
+                        Mode::Check => Some(recorded.check(run.number, *part, answer)),
+                        _ => None,
+                    };
+                    println!("{}", match (&options.format, &check) {
+                        (Format::Human, Some(check)) => report::human_check(*part, answer, check),
+                        (Format::Human, None) => report::human_answer(*part, answer),
+                        (Format::Json, check) =>
+                            report::json_answer(run.number, *part, answer, run.solved.timings.get(*part), check.as_ref()),
+                    });
+                    checks.extend(check);
+                }
+                runs.push(run);
+            }
+            Err(error) => {
+                match options.format {
+                    Format::Human => eprintln!("{}", error),
+                    Format::Json => for part in &Part::all() {
+                        println!("{}", report::json_error(day.number, *part, &error));
+                    },
+                }
+                failed = true;
+            }
+        }
+    }
+
+    if human && !runs.is_empty() {
+        println!("\n{}", runner::summary_table(&runs, options.slow_threshold));
+    }
+
+    match options.mode {
+        Mode::Check => {
+            let count = |outcome: fn(&Check) -> bool| checks.iter().filter(|check| outcome(check)).count();
+            let changed = count(|check| matches!(check, Check::Changed { .. }));
+            note(format!("\n{} passed, {} changed, {} unrecorded, {} failed to run",
+                count(|check| *check == Check::Pass), changed, count(|check| *check == Check::Unrecorded),
+                selected.len() - runs.len()));
+            failed |= changed > 0;
+        }
+        Mode::Record => {
+            for run in &runs {
+                recorded.record(run.number, &run.solved.answers);
+            }
+            match recorded.save(&options.answers_file) {
+                Ok(()) => note(format!("\nRecorded answers for {} days in {}", runs.len(), options.answers_file)),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    failed = true;
+                }
+            }
+        }
+        Mode::Run => {}
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn new_day(argument: &str) {
+    let number = argument.parse::<u32>().unwrap_or_else(|_| {
+        eprintln!("Expecting a day number, got '{}'", argument);
+        process::exit(1);
+    });
+
+    match scaffold::new_day(Path::new(env!("CARGO_MANIFEST_DIR")), number) {
+        Ok(written) => {
+            for path in written {
+                println!("Wrote {}", path);
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+}
+
+fn bench_days(options: &options::Options) {
+    let selection = options.argument.as_ref().expect("bench always has an argument");
+    let exit = |error: String| -> ! {
+        eprintln!("{}", error);
+        process::exit(1);
+    };
+
+    let selected = runner::select_days(selection).unwrap_or_else(|error| exit(error));
+    if selected.len() > 1 && options.input != options::Input::Default {
+        exit("--input can only be used when benchmarking a single day".to_string());
+    }
+    let baseline = options.baseline.as_ref().map(|path| bench::load_baseline(path).unwrap_or_else(|error| exit(error)));
+
+    let mut measurements = vec![];
+    for day in &selected {
+        let input = runner::read_day_input(day.number, &options.input).unwrap_or_else(|error| exit(error));
+        let measured = bench::bench_day(day, &input, options.runs)
+            .map_err(|error| format!("Couldn't parse the input for day {}: {}", day.number, error));
+        measurements.extend(measured.unwrap_or_else(|error| exit(error)));
+    }
+
+    println!("{} runs per part\n{}", options.runs, bench::bench_table(&measurements, baseline.as_ref()));
+    if let Some(path) = &options.save_baseline {
+        bench::save_baseline(path, &measurements).unwrap_or_else(|error| exit(error));
+        println!("\nSaved results to {}", path);
     }
 }
 
-fn get_day() -> i32 {
-    return env::args()
-        .collect::<Vec<String>>()
-        .get(1)
-        .expect("Need to pass one argument as the day number. Example: cargo run 1")
-        .parse::<i32>()
-        .expect("Expecting first argument to be an integer");
-}
\ No newline at end of file
+fn list_days() {
+    let numbers: Vec<String> = days::registry().iter().map(|day| day.number.to_string()).collect();
+    println!("Available days: {}", numbers.join(", "));
+}
@@ -0,0 +1,228 @@
+use answers::DEFAULT_ANSWERS_FILE;
+use bench::DEFAULT_RUNS;
+
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage: cargo run -- <day | first-last | all | list> [--slow <ms>] [--input <path | ->] \
+[--check | --record] [--answers <path>] [--format <human | json>]
+       cargo run -- new <day>
+       cargo run --release -- bench <day | first-last | all> [--runs <n>] [--baseline <path>] [--save-baseline <path>]";
+
+/// Commands that take an argument of their own, like the day for `new`.
+const COMMANDS_WITH_ARGUMENT: &[&str] = &["new", "bench"];
+
+/// Days whose total time is above this are highlighted in the summary.
+const DEFAULT_SLOW_THRESHOLD_MS: u64 = 1000;
+
+/// Where a day's puzzle input is read from.
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    /// `dayN.txt` in the inputs directory.
+    Default,
+    File(String),
+    Stdin,
+}
+
+/// What to do with the answers once the days have run.
+#[derive(Debug, PartialEq)]
+pub enum Mode {
+    Run,
+    /// Compare them against the answers file.
+    Check,
+    /// Write them to the answers file.
+    Record,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Format {
+    Human,
+    /// One JSON object per line for each part.
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub command: String,
+    pub argument: Option<String>,
+    pub slow_threshold: Duration,
+    pub input: Input,
+    pub mode: Mode,
+    pub answers_file: String,
+    pub format: Format,
+    /// How many times `bench` runs each part.
+    pub runs: usize,
+    /// Where `bench` reads results to compare against.
+    pub baseline: Option<String>,
+    /// Where `bench` writes its results.
+    pub save_baseline: Option<String>,
+}
+
+pub fn parse_options(arguments: &[String]) -> Result<Options, String> {
+    let mut command: Option<String> = None;
+    let mut command_argument = None;
+    let mut slow_threshold = Duration::from_millis(DEFAULT_SLOW_THRESHOLD_MS);
+    let mut input = Input::Default;
+    let mut mode = Mode::Run;
+    let mut answers_file = DEFAULT_ANSWERS_FILE.to_string();
+    let mut format = Format::Human;
+    let mut runs = DEFAULT_RUNS;
+    let mut baseline = None;
+    let mut save_baseline = None;
+    let mut arguments = arguments.iter();
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--slow" => {
+                let value = arguments.next().ok_or("Missing value for --slow")?;
+                let millis = value.parse::<u64>()
+                    .map_err(|_| format!("Expecting --slow to be a number of milliseconds, got '{}'", value))?;
+                slow_threshold = Duration::from_millis(millis);
+            }
+            "--input" => {
+                input = match arguments.next().ok_or("Missing value for --input")?.as_str() {
+                    "-" => Input::Stdin,
+                    path => Input::File(path.to_string()),
+                };
+            }
+            "--check" | "--record" => {
+                if mode != Mode::Run {
+                    return Err("Only one of --check and --record can be used".to_string());
+                }
+                mode = if argument == "--check" { Mode::Check } else { Mode::Record };
+            }
+            "--answers" => answers_file = arguments.next().ok_or("Missing value for --answers")?.clone(),
+            "--format" => {
+                format = match arguments.next().ok_or("Missing value for --format")?.as_str() {
+                    "human" => Format::Human,
+                    "json" => Format::Json,
+                    other => return Err(format!("Unknown format {}, expecting human or json", other)),
+                };
+            }
+            "--runs" => {
+                let value = arguments.next().ok_or("Missing value for --runs")?;
+                runs = value.parse::<usize>().ok().filter(|&runs| runs > 0)
+                    .ok_or(format!("Expecting --runs to be a positive number, got '{}'", value))?;
+            }
+            "--baseline" => baseline = Some(arguments.next().ok_or("Missing value for --baseline")?.clone()),
+            "--save-baseline" => save_baseline = Some(arguments.next().ok_or("Missing value for --save-baseline")?.clone()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ if command.as_ref().is_some_and(|command| COMMANDS_WITH_ARGUMENT.contains(&command.as_str()))
+                && command_argument.is_none() => command_argument = Some(argument.clone()),
+            _ if command.is_some() => return Err(format!("Unexpected argument {}", argument)),
+            _ => command = Some(argument.clone()),
+        }
+    }
+
+    let command = command.ok_or("Need to pass a day number, a range of days, all, list, or new")?;
+    if COMMANDS_WITH_ARGUMENT.contains(&command.as_str()) && command_argument.is_none() {
+        return Err(format!("Missing argument for {}", command));
+    }
+    // Recorded answers are for the puzzle inputs, so other inputs can't be
+    // checked against them or recorded over them.
+    if mode != Mode::Run && input != Input::Default {
+        return Err("--input can't be used with --check or --record".to_string());
+    }
+
+    Ok(Options {
+        command,
+        argument: command_argument,
+        slow_threshold,
+        input,
+        mode,
+        answers_file,
+        format,
+        runs,
+        baseline,
+        save_baseline,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(arguments: &[&str]) -> Vec<String> {
+        arguments.iter().map(|argument| argument.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            Ok(Options {
+                command: "all".to_string(),
+                argument: None,
+                slow_threshold: Duration::from_millis(50),
+                input: Input::Default,
+                mode: Mode::Run,
+                answers_file: DEFAULT_ANSWERS_FILE.to_string(),
+                format: Format::Human,
+                runs: DEFAULT_RUNS,
+                baseline: None,
+                save_baseline: None,
+            }),
+            parse_options(&arguments(&["all", "--slow", "50"]))
+        );
+        assert_eq!(
+            Ok(Duration::from_millis(DEFAULT_SLOW_THRESHOLD_MS)),
+            parse_options(&arguments(&["3"])).map(|options| options.slow_threshold)
+        );
+    }
+
+    #[test]
+    fn test_parse_options_input() {
+        assert_eq!(
+            Ok(Input::File("other/day3.txt".to_string())),
+            parse_options(&arguments(&["3", "--input", "other/day3.txt"])).map(|options| options.input)
+        );
+        assert_eq!(Ok(Input::Stdin), parse_options(&arguments(&["--input", "-", "3"])).map(|options| options.input));
+    }
+
+    #[test]
+    fn test_parse_options_mode() {
+        let options = parse_options(&arguments(&["all", "--check", "--answers", "known.toml"])).unwrap();
+        assert_eq!(Mode::Check, options.mode);
+        assert_eq!("known.toml", options.answers_file);
+
+        assert_eq!(Ok(Mode::Record), parse_options(&arguments(&["--record", "2"])).map(|options| options.mode));
+        assert!(parse_options(&arguments(&["2", "--record", "--check"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_format() {
+        assert_eq!(Ok(Format::Json), parse_options(&arguments(&["all", "--format", "json"])).map(|options| options.format));
+        assert!(parse_options(&arguments(&["all", "--format", "xml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_command_argument() {
+        let options = parse_options(&arguments(&["new", "9"])).unwrap();
+        assert_eq!("new", options.command);
+        assert_eq!(Some("9".to_string()), options.argument);
+
+        assert!(parse_options(&arguments(&["new"])).is_err());
+        assert!(parse_options(&arguments(&["new", "9", "10"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_bench() {
+        let options = parse_options(&arguments(&["bench", "1-3", "--runs", "50", "--baseline", "old.txt", "--save-baseline", "new.txt"])).unwrap();
+
+        assert_eq!(Some("1-3".to_string()), options.argument);
+        assert_eq!(50, options.runs);
+        assert_eq!(Some("old.txt".to_string()), options.baseline);
+        assert_eq!(Some("new.txt".to_string()), options.save_baseline);
+        assert!(parse_options(&arguments(&["bench", "all", "--runs", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_errors() {
+        assert!(parse_options(&arguments(&[])).is_err());
+        assert!(parse_options(&arguments(&["1", "2"])).is_err());
+        assert!(parse_options(&arguments(&["1", "--slow"])).is_err());
+        assert!(parse_options(&arguments(&["1", "--slow", "fast"])).is_err());
+        assert!(parse_options(&arguments(&["1", "--verbose"])).is_err());
+        assert!(parse_options(&arguments(&["1", "--input"])).is_err());
+        assert!(parse_options(&arguments(&["1", "--input", "x.txt", "--check"])).is_err());
+        assert!(parse_options(&arguments(&["--record", "1", "--input", "-"])).is_err());
+    }
+}
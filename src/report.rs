@@ -0,0 +1,120 @@
+//! How results are printed: lines for people, or one JSON object per part for
+//! scripts.
+//!
+//! Each JSON record has the day, the part, and a status: `ok` after a plain
+//! run, `pass`, `changed` or `unrecorded` when checking, or `error` if the day
+//! couldn't run. Records with an answer also carry it and how long the part
+//! took; changed ones carry the expected answer; failed ones the error.
+
+extern crate advent;
+use self::advent::*;
+
+use answers::{self, Check};
+
+use std::time::Duration;
+
+pub fn human_answer(part: Part, answer: &Answer) -> String {
+    // Multi-line answers, like rendered images, start on their own line.
+    let answer = answer.as_str();
+    if answer.contains('\n') {
+        format!("{}:\n{}", part, answer)
+    } else {
+        format!("{}: {}", part, answer)
+    }
+}
+
+pub fn human_check(part: Part, answer: &Answer, check: &Check) -> String {
+    match *check {
+        Check::Pass => format!("{}: pass", part),
+        Check::Changed { ref expected } => format!("{}: changed, expected {} but got {}",
+            part, answers::escape(expected.as_str()), answers::escape(answer.as_str())),
+        Check::Unrecorded => format!("{}: unrecorded, got {}", part, answers::escape(answer.as_str())),
+    }
+}
+
+pub fn json_answer(day: u32, part: Part, answer: &Answer, duration: Duration, check: Option<&Check>) -> String {
+    let status = match check {
+        None => "ok",
+        Some(&Check::Pass) => "pass",
+        Some(&Check::Changed { .. }) => "changed",
+        Some(&Check::Unrecorded) => "unrecorded",
+    };
+    let mut record = format!("{{\"day\":{},\"part\":{},\"status\":\"{}\",\"answer\":{},\"duration_ms\":{:.3}",
+        day, part.number(), status, json_string(answer.as_str()), duration.as_secs_f64() * 1000.0);
+    if let Some(Check::Changed { expected }) = check {
+        record.push_str(&format!(",\"expected\":{}", json_string(expected.as_str())));
+    }
+    record.push('}');
+    record
+}
+
+pub fn json_error(day: u32, part: Part, error: &str) -> String {
+    format!("{{\"day\":{},\"part\":{},\"status\":\"error\",\"error\":{}}}", day, part.number(), json_string(error))
+}
+
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_answer() {
+        assert_eq!("Part one: 42", human_answer(Part::One, &Answer::from(42)));
+        assert_eq!("Part two:\n# \n #", human_answer(Part::Two, &Answer::from("# \n #")));
+    }
+
+    #[test]
+    fn test_human_check() {
+        let answer = Answer::from(7621);
+
+        assert_eq!("Part two: pass", human_check(Part::Two, &answer, &Check::Pass));
+        assert_eq!(
+            "Part two: changed, expected \"7622\" but got \"7621\"",
+            human_check(Part::Two, &answer, &Check::Changed { expected: Answer::from(7622) })
+        );
+    }
+
+    #[test]
+    fn test_json_answer() {
+        let duration = Duration::from_micros(1500);
+
+        assert_eq!(
+            r#"{"day":2,"part":1,"status":"ok","answer":"3306701","duration_ms":1.500}"#,
+            json_answer(2, Part::One, &Answer::from(3306701), duration, None)
+        );
+        assert_eq!(
+            r#"{"day":2,"part":2,"status":"changed","answer":"7621","duration_ms":1.500,"expected":"7622"}"#,
+            json_answer(2, Part::Two, &Answer::from(7621), duration, Some(&Check::Changed { expected: Answer::from(7622) }))
+        );
+    }
+
+    #[test]
+    fn test_json_error() {
+        assert_eq!(
+            r#"{"day":7,"part":1,"status":"error","error":"Couldn't read \"day7.txt\""}"#,
+            json_error(7, Part::One, "Couldn't read \"day7.txt\"")
+        );
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(r#""a\nb\\c\u0001""#, json_string("a\nb\\c\u{1}"));
+        assert_eq!("\"█\"", json_string("█"));
+    }
+}
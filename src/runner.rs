@@ -0,0 +1,179 @@
+extern crate advent;
+use self::advent::*;
+use self::advent::inputs::InputManager;
+
+use days::{self, Day};
+use options::Input;
+
+use ansi_term::Colour::Red;
+use std::env;
+use std::io::{self, Read};
+use std::time::Duration;
+
+const DEFAULT_INPUT_DIR: &str = "inputs";
+
+/// Picks the days to run from a single day number, an inclusive range like
+/// `1-8`, or `all`. Ranges skip days that aren't registered.
+pub fn select_days(selection: &str) -> Result<Vec<Day>, String> {
+    if selection == "all" {
+        return Ok(days::registry());
+    }
+
+    let (first, last) = match selection.find('-') {
+        Some(index) => (parse_day_number(&selection[..index])?, parse_day_number(&selection[index + 1..])?),
+        None => {
+            let number = parse_day_number(selection)?;
+            return days::find_day(number)
+                .map(|day| vec![day])
+                .ok_or(format!("Unknown day - {}", number));
+        }
+    };
+
+    let selected: Vec<Day> = days::registry().into_iter()
+        .filter(|day| first <= day.number && day.number <= last)
+        .collect();
+    if selected.is_empty() {
+        return Err(format!("No days between {} and {}", first, last));
+    }
+    Ok(selected)
+}
+
+fn parse_day_number(number: &str) -> Result<u32, String> {
+    number.trim().parse::<u32>()
+        .map_err(|_| format!("Expecting a day number, a range like 1-8, or all, got '{}'", number))
+}
+
+pub struct DayRun {
+    pub number: u32,
+    pub solved: Solved,
+}
+
+pub fn run_day(day: &Day, input: &Input) -> Result<DayRun, String> {
+    let input = read_day_input(day.number, input)?;
+
+    Ok(DayRun {
+        number: day.number,
+        solved: (day.solve)(&input).map_err(|error| format!("Couldn't parse the input for day {}: {}", day.number, error))?,
+    })
+}
+
+/// Reads the puzzle input for a day. By default that's `dayN.txt` in the
+/// directory named by `AOC_INPUT_DIR`, or `inputs` if it isn't set, fetched
+/// there first if it's missing and a session token is configured.
+pub fn read_day_input(number: u32, input: &Input) -> Result<String, String> {
+    match *input {
+        Input::Default => {
+            let directory = env::var("AOC_INPUT_DIR").unwrap_or_else(|_| DEFAULT_INPUT_DIR.to_string());
+            InputManager::from_env(directory).input(number)
+        }
+        Input::File(ref path) => read_inputs(path),
+        Input::Stdin => {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)
+                .map_err(|error| format!("Couldn't read standard input: {}", error))?;
+            Ok(contents)
+        }
+    }
+}
+
+/// A table of how long each day took to parse and solve, with a row of totals.
+/// Days slower than `slow_threshold` are highlighted in red.
+pub fn summary_table(runs: &[DayRun], slow_threshold: Duration) -> String {
+    let mut lines = vec![format!("{:<5} {:>12} {:>12} {:>12} {:>12}", "Day", "Parse", "Part one", "Part two", "Total")];
+
+    for run in runs {
+        let timings = &run.solved.timings;
+        let line = timings_row(&run.number.to_string(), timings.parse, timings.part_one, timings.part_two, timings.total());
+        if timings.total() > slow_threshold {
+            lines.push(Red.bold().paint(line).to_string());
+        } else {
+            lines.push(line);
+        }
+    }
+
+    let sum = |part: fn(&Timings) -> Duration| runs.iter().map(|run| part(&run.solved.timings)).sum::<Duration>();
+    lines.push(timings_row(
+        "Total",
+        sum(|timings| timings.parse),
+        sum(|timings| timings.part_one),
+        sum(|timings| timings.part_two),
+        sum(|timings| timings.total()),
+    ));
+
+    lines.join("\n")
+}
+
+fn timings_row(label: &str, parse: Duration, part_one: Duration, part_two: Duration, total: Duration) -> String {
+    format!("{:<5} {:>12} {:>12} {:>12} {:>12}",
+        label, format_duration(parse), format_duration(part_one), format_duration(part_two), format_duration(total))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(days: Result<Vec<Day>, String>) -> Result<Vec<u32>, String> {
+        days.map(|days| days.iter().map(|day| day.number).collect())
+    }
+
+    fn day_run(number: u32, millis: u64) -> DayRun {
+        DayRun {
+            number,
+            solved: Solved {
+                answers: Answers { part_one: Answer::from(1), part_two: Answer::from(2) },
+                timings: Timings {
+                    parse: Duration::from_millis(millis),
+                    part_one: Duration::from_millis(millis),
+                    part_two: Duration::from_millis(millis),
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_select_days() {
+        assert_eq!(numbers(Ok(days::registry())), numbers(select_days("all")));
+        assert_eq!(Ok(vec![1, 2, 3, 4, 5, 6, 8]), numbers(select_days("1-8")));
+        assert_eq!(Ok(vec![5, 6, 8]), numbers(select_days("5-8")));
+        assert_eq!(Ok(vec![3]), numbers(select_days("3")));
+    }
+
+    #[test]
+    fn test_select_days_errors() {
+        assert_eq!(Err("Unknown day - 7".to_string()), numbers(select_days("7")));
+        assert_eq!(Err("No days between 20 and 25".to_string()), numbers(select_days("20-25")));
+        assert!(select_days("one").is_err());
+        assert!(select_days("1-").is_err());
+    }
+
+    #[test]
+    fn test_read_day_input() {
+        let input = read_day_input(1, &Input::File("inputs/day1.txt".to_string()));
+        assert_eq!(read_inputs("inputs/day1.txt"), input);
+
+        let error = read_day_input(1, &Input::File("missing/day1.txt".to_string())).unwrap_err();
+        assert!(error.starts_with("Couldn't read missing/day1.txt"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("1.500 ms", format_duration(Duration::from_micros(1500)));
+        assert_eq!("0.000 ms", format_duration(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn test_summary_table() {
+        let table = summary_table(&[day_run(1, 1), day_run(2, 100)], Duration::from_millis(200));
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(4, lines.len());
+        assert_eq!("1         1.000 ms     1.000 ms     1.000 ms     3.000 ms", lines[1]);
+        assert!(lines[2].starts_with("\u{1b}["));
+        assert!(lines[2].contains("300.000 ms"));
+        assert_eq!("Total   101.000 ms   101.000 ms   101.000 ms   303.000 ms", lines[3]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Generates a new day: its module from a template, its line in the `days!`
+//! registry and an empty input file.
+
+use std::fs;
+use std::path::Path;
+
+const REGISTRY_START: &str = "days! {\n";
+
+pub fn day_template(number: u32) -> String {
+    format!(r#"extern crate advent;
+use self::advent::*;
+
+pub struct Day{n};
+
+impl Solution for Day{n} {{
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {{
+        parse_lines(input)
+    }}
+
+    fn part_one(inputs: &Vec<String>) -> Answer {{
+        inputs.len().into()
+    }}
+
+    fn part_two(inputs: &Vec<String>) -> Answer {{
+        inputs.len().into()
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    const EXAMPLE: &str = "";
+
+    #[test]
+    fn test_solve() {{
+        let answers = solve::<Day{n}>(EXAMPLE).unwrap();
+
+        assert_eq!(Answer::from(0), answers.part_one);
+        assert_eq!(Answer::from(0), answers.part_two);
+    }}
+}}
+"#, n = number)
+}
+
+/// Adds `number => dayN::DayN` to the `days!` registry in `source`, keeping
+/// the entries in order.
+pub fn register_day(source: &str, number: u32) -> Result<String, String> {
+    let start = source.find(REGISTRY_START).ok_or("Couldn't find the days! registry")? + REGISTRY_START.len();
+    let end = start + source[start..].find('}').ok_or("Couldn't find the end of the days! registry")?;
+
+    let mut insert_at = end;
+    let mut offset = start;
+    for line in source[start..end].split_inclusive('\n') {
+        let registered = line.split("=>").next().and_then(|number| number.trim().parse::<u32>().ok());
+        match registered {
+            Some(registered) if registered == number => return Err(format!("Day {} is already registered", number)),
+            Some(registered) if registered > number => {
+                insert_at = offset;
+                break;
+            }
+            _ => offset += line.len(),
+        }
+    }
+
+    let entry = format!("    {} => day{}::Day{},\n", number, number, number);
+    Ok(format!("{}{}{}", &source[..insert_at], entry, &source[insert_at..]))
+}
+
+/// Creates day `number` in the crate at `root`, returning the files it wrote.
+/// Nothing is touched if the day's module already exists or is registered,
+/// and an existing input file is left as it is.
+pub fn new_day(root: &Path, number: u32) -> Result<Vec<String>, String> {
+    if number == 0 || number > 25 {
+        return Err(format!("Expecting a day between 1 and 25, got {}", number));
+    }
+
+    let module = root.join("src").join("days").join(format!("day{}.rs", number));
+    let registry = root.join("src").join("days.rs");
+    let input = root.join("inputs").join(format!("day{}.txt", number));
+
+    if module.exists() {
+        return Err(format!("{} already exists", module.display()));
+    }
+    let source = fs::read_to_string(&registry).map_err(|error| format!("Couldn't read {}: {}", registry.display(), error))?;
+    let registered = register_day(&source, number)?;
+
+    let mut written = vec![];
+    write(&module, &day_template(number), &mut written)?;
+    write(&registry, &registered, &mut written)?;
+    if !input.exists() {
+        fs::create_dir_all(root.join("inputs")).map_err(|error| format!("Couldn't create inputs directory: {}", error))?;
+        write(&input, "", &mut written)?;
+    }
+    Ok(written)
+}
+
+fn write(path: &Path, contents: &str, written: &mut Vec<String>) -> Result<(), String> {
+    fs::write(path, contents).map_err(|error| format!("Couldn't write {}: {}", path.display(), error))?;
+    written.push(path.display().to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    const REGISTRY: &str = "days! {\n    1 => day1::Day1,\n    3 => day3::Day3,\n}\n";
+
+    fn scratch_crate(name: &str) -> std::path::PathBuf {
+        let root = env::temp_dir().join(format!("advent-scaffold-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src").join("days")).unwrap();
+        fs::write(root.join("src").join("days.rs"), REGISTRY).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_register_day() {
+        assert_eq!(
+            Ok("days! {\n    1 => day1::Day1,\n    2 => day2::Day2,\n    3 => day3::Day3,\n}\n".to_string()),
+            register_day(REGISTRY, 2)
+        );
+        assert_eq!(
+            Ok("days! {\n    1 => day1::Day1,\n    3 => day3::Day3,\n    10 => day10::Day10,\n}\n".to_string()),
+            register_day(REGISTRY, 10)
+        );
+        assert_eq!(Err("Day 3 is already registered".to_string()), register_day(REGISTRY, 3));
+        assert!(register_day("fn main() {}", 2).is_err());
+    }
+
+    #[test]
+    fn test_day_template() {
+        let template = day_template(12);
+
+        assert!(template.starts_with("extern crate advent;\nuse self::advent::*;\n"));
+        assert!(template.contains("impl Solution for Day12 {"));
+        assert!(template.contains("solve::<Day12>(EXAMPLE).unwrap()"));
+    }
+
+    #[test]
+    fn test_new_day() {
+        let root = scratch_crate("new");
+
+        let written = new_day(&root, 2).unwrap();
+
+        assert_eq!(3, written.len());
+        assert_eq!(day_template(2), fs::read_to_string(root.join("src/days/day2.rs")).unwrap());
+        assert!(fs::read_to_string(root.join("src/days.rs")).unwrap().contains("2 => day2::Day2"));
+        assert_eq!("", fs::read_to_string(root.join("inputs/day2.txt")).unwrap());
+
+        assert!(new_day(&root, 2).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_new_day_refuses_to_overwrite() {
+        let root = scratch_crate("overwrite");
+        fs::write(root.join("src/days/day4.rs"), "// hand written").unwrap();
+
+        assert!(new_day(&root, 4).is_err());
+        assert!(new_day(&root, 3).is_err());
+        assert!(new_day(&root, 26).is_err());
+        assert_eq!("// hand written", fs::read_to_string(root.join("src/days/day4.rs")).unwrap());
+        assert_eq!(REGISTRY, fs::read_to_string(root.join("src/days.rs")).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_new_day_keeps_existing_input() {
+        let root = scratch_crate("input");
+        fs::create_dir_all(root.join("inputs")).unwrap();
+        fs::write(root.join("inputs/day5.txt"), "1,2,3").unwrap();
+
+        assert_eq!(2, new_day(&root, 5).unwrap().len());
+        assert_eq!("1,2,3", fs::read_to_string(root.join("inputs/day5.txt")).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
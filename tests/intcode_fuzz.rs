@@ -0,0 +1,505 @@
+// Differential fuzzing of the Intcode VM.
+//
+// Random, valid Intcode programs are generated from a seeded PRNG and run both
+// through `advent::intcode::run_program` and through the deliberately simple
+// reference interpreter below. Memory, outputs and errors must match, and the
+// optimiser must not change what a program outputs. When a case disagrees it
+// is minimised before being reported, together with the seed needed to
+// reproduce it.
+//
+// The number of cases and the seed can be overridden with the
+// `INTCODE_FUZZ_CASES` and `INTCODE_FUZZ_SEED` environment variables.
+
+extern crate advent;
+use advent::intcode;
+use advent::intcode::Decoding;
+use advent::intcode::optimiser;
+
+use std::cell::Cell;
+use std::env;
+use std::panic;
+use std::sync::Once;
+
+const DEFAULT_CASES: usize = 500;
+const DEFAULT_SEED: u64 = 0x2019_0005;
+const MAX_INSTRUCTIONS: usize = 24;
+const MAX_DATA: usize = 8;
+const VALUE_RANGE: i64 = 50;
+
+fn cases_and_seed() -> (usize, u64) {
+    let cases = env::var("INTCODE_FUZZ_CASES").ok().and_then(|c| c.parse().ok()).unwrap_or(DEFAULT_CASES);
+    let seed = env::var("INTCODE_FUZZ_SEED").ok().and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_SEED);
+    (cases, seed)
+}
+
+#[test]
+fn fuzz_vm_against_reference() {
+    let (cases, seed) = cases_and_seed();
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let program = Program::generate(&mut rng);
+        let input = rng.range(-VALUE_RANGE, VALUE_RANGE);
+
+        if compare(&program, input).is_some() {
+            let minimised = minimise(program, |p| compare(p, input).is_some());
+            panic!(
+                "VM and reference disagree (seed {}, case {})\nminimised program: {:?}\ninput: {}\nmismatch: {}",
+                seed, case, minimised.encode(), input, compare(&minimised, input).unwrap()
+            );
+        }
+    }
+}
+
+// Strict decoding must reject exactly the encodings the reference rejects, so
+// valid programs get random digits added to one of their instructions.
+#[test]
+fn fuzz_strict_vm_against_reference_with_noisy_encodings() {
+    let (cases, seed) = cases_and_seed();
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let mut program = Program::generate(&mut rng);
+        if !program.instructions.is_empty() {
+            let index = rng.index(program.instructions.len());
+            program.noise[index] = rng.range(1, 9) * 10_i64.pow(rng.range(2, 5) as u32);
+        }
+        let input = rng.range(-VALUE_RANGE, VALUE_RANGE);
+
+        if compare_strict(&program, input).is_some() {
+            let minimised = minimise(program, |p| compare_strict(p, input).is_some());
+            panic!(
+                "Strict VM and reference disagree (seed {}, case {})\nminimised program: {:?}\ninput: {}\nmismatch: {}",
+                seed, case, minimised.encode(), input, compare_strict(&minimised, input).unwrap()
+            );
+        }
+    }
+}
+
+// Optimised images must output the same as the originals.
+#[test]
+fn fuzz_optimiser_against_reference() {
+    let (cases, seed) = cases_and_seed();
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let program = Program::generate(&mut rng);
+        let input = rng.range(-VALUE_RANGE, VALUE_RANGE);
+
+        if compare_optimised(&program, input).is_some() {
+            let minimised = minimise(program, |p| compare_optimised(p, input).is_some());
+            panic!(
+                "Optimised program disagrees with reference (seed {}, case {})\nminimised program: {:?}\ninput: {}\nmismatch: {}",
+                seed, case, minimised.encode(), input, compare_optimised(&minimised, input).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn reference_matches_known_programs() {
+    let equals_eight = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+    assert_eq!(Ok(vec![1]), reference_run(&equals_eight, 8).map(|r| r.output));
+    assert_eq!(Ok(vec![0]), reference_run(&equals_eight, 7).map(|r| r.output));
+
+    let simple = vec![1, 9, 10, 11, 2, 11, 11, 11, 99, 1, 2, 999];
+    assert_eq!(Ok(vec![1, 9, 10, 11, 2, 11, 11, 11, 99, 1, 2, 9]), reference_run(&simple, 0).map(|r| r.memory));
+
+    assert!(reference_run(&[30001, 0, 0, 0, 99], 0).is_err());
+}
+
+#[test]
+fn minimise_removes_irrelevant_instructions() {
+    let mut rng = Rng::new(7);
+    let program = (0..100)
+        .map(|_| Program::generate(&mut rng))
+        .find(|p| p.instructions.len() > 5 && p.instructions.iter().any(|i| i.is_output()))
+        .expect("Couldn't generate a program with an output");
+
+    let minimised = minimise(program, |p| p.instructions.iter().any(|i| i.is_output()));
+
+    assert_eq!(1, minimised.instructions.len());
+    assert!(minimised.instructions[0].is_output());
+}
+
+fn compare(program: &Program, input: i64) -> Option<String> {
+    compare_with_decoding(program, input, Decoding::Lenient)
+}
+
+fn compare_strict(program: &Program, input: i64) -> Option<String> {
+    compare_with_decoding(program, input, Decoding::Strict)
+}
+
+// Only outputs are compared, as the optimiser may move data around. Programs
+// that fail are skipped.
+fn compare_optimised(program: &Program, input: i64) -> Option<String> {
+    let memory = program.encode();
+    let optimised = optimiser::optimise(&memory).memory;
+
+    match (reference_run(&memory, input), vm_run(&optimised, input, Decoding::Lenient)) {
+        (Ok(expected), Ok(actual)) if expected.output != actual.output =>
+            Some(format!("optimised {:?} output {:?} != reference {:?}", optimised, actual.output, expected.output)),
+        (Ok(_), Err(error)) => Some(format!("optimised {:?} failed with '{}' but reference succeeded", optimised, error)),
+        _ => None,
+    }
+}
+
+fn compare_with_decoding(program: &Program, input: i64, decoding: Decoding) -> Option<String> {
+    let memory = program.encode();
+    let expected = reference_run(&memory, input);
+    let actual = vm_run(&memory, input, decoding);
+
+    match (expected, actual) {
+        (Ok(expected), Ok(actual)) => {
+            if expected.output != actual.output {
+                Some(format!("output {:?} != reference {:?}", actual.output, expected.output))
+            } else if expected.memory != actual.memory {
+                Some(format!("memory {:?} != reference {:?}", actual.memory, expected.memory))
+            } else {
+                None
+            }
+        }
+        (Err(_), Err(_)) => None,
+        (Ok(_), Err(error)) => Some(format!("VM failed with '{}' but reference succeeded", error)),
+        (Err(error), Ok(_)) => Some(format!("VM succeeded but reference failed with '{}'", error)),
+    }
+}
+
+thread_local! {
+    static IN_VM: Cell<bool> = const { Cell::new(false) };
+}
+
+// The panic hook is shared by every test thread, so it's replaced once with
+// one that only keeps quiet about panics caught by `vm_run` on its own thread.
+fn silence_vm_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !IN_VM.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+fn vm_run(memory: &[i64], input: i64, decoding: Decoding) -> Result<Execution, String> {
+    silence_vm_panics();
+    IN_VM.with(|in_vm| in_vm.set(true));
+    let result = panic::catch_unwind(|| intcode::run_program_with_decoding(memory, &input, decoding));
+    IN_VM.with(|in_vm| in_vm.set(false));
+
+    match result {
+        Ok(Ok(r)) => Ok(Execution { memory: r.memory, output: r.output }),
+        Ok(Err(error)) => Err(error.to_string()),
+        Err(e) => Err(e.downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Execution {
+    memory: Vec<i64>,
+    output: Vec<i64>,
+}
+
+// A direct transcription of the day 2 and day 5 specifications. Anything the
+// specification doesn't define is an error.
+fn reference_run(memory: &[i64], input: i64) -> Result<Execution, String> {
+    let mut memory = memory.to_vec();
+    let mut output = vec![];
+    let mut ip = 0;
+
+    loop {
+        let instruction = read(&memory, ip)?;
+        let opcode = instruction % 100;
+        let mode = |parameter: u32| (instruction / 10_i64.pow(parameter + 1)) % 10;
+        let arity = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            3 | 4 => 1,
+            5 | 6 => 2,
+            99 => 0,
+            _ => return Err(format!("unknown opcode {} at {}", opcode, ip)),
+        };
+        if instruction / 10_i64.pow(arity + 2) != 0 {
+            return Err(format!("unused mode digits in {} at {}", instruction, ip));
+        }
+
+        let mut values = vec![];
+        for parameter in 1..=arity {
+            let raw = read(&memory, ip + parameter as usize)?;
+            values.push(match mode(parameter) {
+                0 => read(&memory, address(raw)?)?,
+                1 => raw,
+                m => return Err(format!("unknown mode {} in {} at {}", m, instruction, ip)),
+            });
+        }
+        let target = |parameter: u32| -> Result<usize, String> {
+            if mode(parameter) != 0 {
+                return Err(format!("write parameter in immediate mode in {} at {}", instruction, ip));
+            }
+            address(read(&memory, ip + parameter as usize)?)
+        };
+
+        let mut next = ip + arity as usize + 1;
+        match opcode {
+            1 => { let t = target(3)?; write(&mut memory, t, arithmetic(values[0], values[1], i64::checked_add, i64::wrapping_add)?)?; }
+            2 => { let t = target(3)?; write(&mut memory, t, arithmetic(values[0], values[1], i64::checked_mul, i64::wrapping_mul)?)?; }
+            3 => { let t = target(1)?; write(&mut memory, t, input)?; }
+            4 => output.push(values[0]),
+            5 => if values[0] != 0 { next = address(values[1])? },
+            6 => if values[0] == 0 { next = address(values[1])? },
+            7 => { let t = target(3)?; write(&mut memory, t, (values[0] < values[1]) as i64)?; }
+            8 => { let t = target(3)?; write(&mut memory, t, (values[0] == values[1]) as i64)?; }
+            _ => return Ok(Execution { memory, output }),
+        }
+        ip = next;
+    }
+}
+
+// The VM uses plain arithmetic, which panics on overflow in debug builds and
+// wraps in release builds.
+fn arithmetic(a: i64, b: i64, checked: fn(i64, i64) -> Option<i64>, wrapping: fn(i64, i64) -> i64) -> Result<i64, String> {
+    if cfg!(debug_assertions) {
+        checked(a, b).ok_or_else(|| format!("overflow in {} and {}", a, b))
+    } else {
+        Ok(wrapping(a, b))
+    }
+}
+
+fn address(value: i64) -> Result<usize, String> {
+    if value < 0 { Err(format!("negative address {}", value)) } else { Ok(value as usize) }
+}
+
+fn read(memory: &[i64], address: usize) -> Result<i64, String> {
+    memory.get(address).cloned().ok_or_else(|| format!("read out of bounds at {}", address))
+}
+
+fn write(memory: &mut [i64], address: usize, value: i64) -> Result<(), String> {
+    let cell = memory.get_mut(address).ok_or_else(|| format!("write out of bounds at {}", address))?;
+    *cell = value;
+    Ok(())
+}
+
+// Programs are generated structurally so that they are always valid and always
+// terminate: reads and writes only touch the data region placed after the
+// final halt, and jumps only ever go forward to an instruction boundary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Immediate(i64),
+    Data(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction {
+    Add(Operand, Operand, usize),
+    Multiply(Operand, Operand, usize),
+    Input(usize),
+    Output(Operand),
+    JumpIfTrue(Operand, usize),
+    JumpIfFalse(Operand, usize),
+    LessThan(Operand, Operand, usize),
+    Equals(Operand, Operand, usize),
+}
+
+impl Instruction {
+    fn length(&self) -> usize {
+        match self {
+            Instruction::Input(_) | Instruction::Output(_) => 2,
+            Instruction::JumpIfTrue(_, _) | Instruction::JumpIfFalse(_, _) => 3,
+            _ => 4,
+        }
+    }
+
+    fn is_output(&self) -> bool {
+        matches!(self, Instruction::Output(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Program {
+    instructions: Vec<Instruction>,
+    // Added to each encoded instruction, so that invalid encodings can be
+    // built on top of valid programs.
+    noise: Vec<i64>,
+    data: Vec<i64>,
+}
+
+impl Program {
+    fn generate(rng: &mut Rng) -> Program {
+        let data: Vec<i64> = (0..rng.index(MAX_DATA) + 1).map(|_| rng.range(-VALUE_RANGE, VALUE_RANGE)).collect();
+        let length = rng.index(MAX_INSTRUCTIONS + 1);
+
+        let instructions = (0..length).map(|index| {
+            let operand = |rng: &mut Rng| if rng.index(2) == 0 {
+                Operand::Immediate(rng.range(-VALUE_RANGE, VALUE_RANGE))
+            } else {
+                Operand::Data(rng.index(data.len()))
+            };
+            let target = |rng: &mut Rng| index + 1 + rng.index(length - index);
+            let cell = |rng: &mut Rng| rng.index(data.len());
+
+            match rng.index(8) {
+                0 => Instruction::Add(operand(rng), operand(rng), cell(rng)),
+                1 => Instruction::Multiply(operand(rng), operand(rng), cell(rng)),
+                2 => Instruction::Input(cell(rng)),
+                3 => Instruction::Output(operand(rng)),
+                4 => Instruction::JumpIfTrue(operand(rng), target(rng)),
+                5 => Instruction::JumpIfFalse(operand(rng), target(rng)),
+                6 => Instruction::LessThan(operand(rng), operand(rng), cell(rng)),
+                _ => Instruction::Equals(operand(rng), operand(rng), cell(rng)),
+            }
+        }).collect();
+
+        Program { noise: vec![0; length], instructions, data }
+    }
+
+    fn encode(&self) -> Vec<i64> {
+        let mut addresses = vec![0];
+        for instruction in &self.instructions {
+            let last = *addresses.last().unwrap();
+            addresses.push(last + instruction.length());
+        }
+        let data_start = addresses.last().unwrap() + 1;
+
+        let mut memory = vec![];
+        for (instruction, noise) in self.instructions.iter().zip(&self.noise) {
+            let (opcode, operands, destination): (i64, Vec<Operand>, Option<i64>) = match *instruction {
+                Instruction::Add(a, b, c) => (1, vec![a, b], Some((data_start + c) as i64)),
+                Instruction::Multiply(a, b, c) => (2, vec![a, b], Some((data_start + c) as i64)),
+                Instruction::Input(c) => (3, vec![], Some((data_start + c) as i64)),
+                Instruction::Output(a) => (4, vec![a], None),
+                Instruction::JumpIfTrue(a, t) => (5, vec![a, Operand::Immediate(addresses[t] as i64)], None),
+                Instruction::JumpIfFalse(a, t) => (6, vec![a, Operand::Immediate(addresses[t] as i64)], None),
+                Instruction::LessThan(a, b, c) => (7, vec![a, b], Some((data_start + c) as i64)),
+                Instruction::Equals(a, b, c) => (8, vec![a, b], Some((data_start + c) as i64)),
+            };
+
+            let modes = operands.iter().enumerate().fold(0, |modes, (i, operand)| match operand {
+                Operand::Immediate(_) => modes + 10_i64.pow(i as u32 + 2),
+                Operand::Data(_) => modes,
+            });
+            memory.push(modes + opcode + noise);
+            memory.extend(operands.iter().map(|operand| match *operand {
+                Operand::Immediate(value) => value,
+                Operand::Data(cell) => (data_start + cell) as i64,
+            }));
+            memory.extend(destination);
+        }
+        memory.push(99);
+        memory.extend(&self.data);
+        memory
+    }
+
+    // Candidate simplifications, roughly from the most to the least aggressive.
+    fn shrink(&self) -> Vec<Program> {
+        let mut candidates = vec![];
+
+        for index in 0..self.instructions.len() {
+            let mut candidate = self.clone();
+            candidate.instructions.remove(index);
+            candidate.noise.remove(index);
+            for instruction in candidate.instructions.iter_mut() {
+                match instruction {
+                    Instruction::JumpIfTrue(_, target) | Instruction::JumpIfFalse(_, target) if *target > index => *target -= 1,
+                    _ => {}
+                }
+            }
+            candidates.push(candidate);
+        }
+
+        for index in 0..self.instructions.len() {
+            let simplified = match self.instructions[index] {
+                Instruction::Add(a, b, c) => shrink_operands(a, b).into_iter().map(|(a, b)| Instruction::Add(a, b, c)).collect(),
+                Instruction::Multiply(a, b, c) => shrink_operands(a, b).into_iter().map(|(a, b)| Instruction::Multiply(a, b, c)).collect(),
+                Instruction::LessThan(a, b, c) => shrink_operands(a, b).into_iter().map(|(a, b)| Instruction::LessThan(a, b, c)).collect(),
+                Instruction::Equals(a, b, c) => shrink_operands(a, b).into_iter().map(|(a, b)| Instruction::Equals(a, b, c)).collect(),
+                Instruction::Output(a) => shrink_operand(a).into_iter().map(Instruction::Output).collect(),
+                Instruction::JumpIfTrue(a, t) => shrink_operand(a).into_iter().map(|a| Instruction::JumpIfTrue(a, t)).collect(),
+                Instruction::JumpIfFalse(a, t) => shrink_operand(a).into_iter().map(|a| Instruction::JumpIfFalse(a, t)).collect(),
+                Instruction::Input(_) => vec![],
+            };
+            for instruction in simplified {
+                let mut candidate = self.clone();
+                candidate.instructions[index] = instruction;
+                candidates.push(candidate);
+            }
+        }
+
+        for index in 0..self.noise.len() {
+            if self.noise[index] != 0 {
+                let mut candidate = self.clone();
+                candidate.noise[index] = 0;
+                candidates.push(candidate);
+            }
+        }
+
+        for index in 0..self.data.len() {
+            for value in shrink_value(self.data[index]) {
+                let mut candidate = self.clone();
+                candidate.data[index] = value;
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+}
+
+fn shrink_operands(a: Operand, b: Operand) -> Vec<(Operand, Operand)> {
+    let mut candidates: Vec<(Operand, Operand)> = shrink_operand(a).into_iter().map(|a| (a, b)).collect();
+    candidates.extend(shrink_operand(b).into_iter().map(|b| (a, b)));
+    candidates
+}
+
+fn shrink_operand(operand: Operand) -> Vec<Operand> {
+    match operand {
+        Operand::Immediate(value) => shrink_value(value).into_iter().map(Operand::Immediate).collect(),
+        Operand::Data(_) => vec![Operand::Immediate(0)],
+    }
+}
+
+fn shrink_value(value: i64) -> Vec<i64> {
+    match value {
+        0 => vec![],
+        _ => vec![0, value / 2].into_iter().filter(|v| *v != value).collect(),
+    }
+}
+
+// Greedily applies the first simplification that still fails until none does.
+fn minimise<F: Fn(&Program) -> bool>(program: Program, fails: F) -> Program {
+    let mut current = program;
+    while let Some(smaller) = current.shrink().into_iter().find(|candidate| fails(candidate)) {
+        current = smaller;
+    }
+    current
+}
+
+// xorshift64*, enough to make cases reproducible from a seed without pulling
+// in a dependency.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng { state: seed.max(1) }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn index(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn range(&mut self, min: i64, max: i64) -> i64 {
+        min + (self.next() % (max - min + 1) as u64) as i64
+    }
+}